@@ -0,0 +1,67 @@
+//! Compares re-routing mobs with A* against looking them up in flow fields.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ggez::nalgebra as na;
+
+use ggez_game_template::game::board::Board;
+use ggez_game_template::game::unit::Unit;
+
+const MOB_COUNT: i32 = 500;
+
+/// A board with a few walls, so paths actually have to wind around something.
+fn make_maze() -> Board {
+    let mut board = Board::default();
+
+    for y in 8..30 {
+        for x in &[10, 26] {
+            let _ = board.place(Unit {
+                tile_position: na::Point2::new(*x, y),
+                ..Unit::default()
+            });
+        }
+    }
+
+    board
+}
+
+fn mob_positions() -> Vec<na::Point2<i32>> {
+    (0..MOB_COUNT)
+        .map(|i| na::Point2::new(i % 40, (i / 40) * 3 % 40))
+        .filter(|point| *point != na::Point2::new(33, 19))
+        .collect()
+}
+
+fn navigation(c: &mut Criterion) {
+    let board = make_maze();
+    let mobs = mob_positions();
+    let goal = na::Point2::new(33, 19);
+
+    c.bench_function("astar: recompute lane paths", |b| {
+        b.iter(|| board.calculate_paths())
+    });
+
+    c.bench_function("flow field: recompute lane fields", |b| {
+        b.iter(|| board.calculate_flow_fields())
+    });
+
+    c.bench_function("astar: re-route 500 mobs", |b| {
+        b.iter(|| {
+            mobs.iter()
+                .filter_map(|mob| board.calculate_path(mob, &goal))
+                .count()
+        })
+    });
+
+    let fields = board.calculate_flow_fields();
+
+    c.bench_function("flow field: next step for 500 mobs", |b| {
+        b.iter(|| {
+            mobs.iter()
+                .filter_map(|mob| fields[0].next_step(mob))
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, navigation);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+//! Compares finding a target for every tower by looping over every mob against
+//! querying the spatial grid that `Board::update` uses.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ggez::nalgebra as na;
+
+use ggez_game_template::game::spatial::SpatialGrid;
+
+const MOB_COUNT: usize = 500;
+const TOWER_COUNT: usize = 40;
+const RANGE: f32 = 48.0;
+
+/// Spreads points over the 640x640 board without needing a random number generator.
+fn scatter(count: usize, seed: usize) -> Vec<na::Point2<f32>> {
+    (0..count)
+        .map(|i| {
+            let n = (i * 7919 + seed * 104_729) % 409_600;
+            na::Point2::new((n % 640) as f32, (n / 640) as f32)
+        })
+        .collect()
+}
+
+fn targeting(c: &mut Criterion) {
+    let mobs = scatter(MOB_COUNT, 1);
+    let towers = scatter(TOWER_COUNT, 2);
+
+    c.bench_function("targeting: every tower checks every mob", |b| {
+        b.iter(|| {
+            towers
+                .iter()
+                .filter_map(|tower| {
+                    mobs.iter()
+                        .position(|mob| na::distance(mob, tower) <= RANGE)
+                })
+                .count()
+        })
+    });
+
+    let mut grid = SpatialGrid::new(640.0, 640.0, 32.0);
+
+    c.bench_function("targeting: rebuild grid and query per tower", |b| {
+        b.iter(|| {
+            grid.rebuild(mobs.iter().cloned());
+
+            towers
+                .iter()
+                .filter_map(|tower| {
+                    grid.query(tower, RANGE)
+                        .filter(|index| na::distance(&mobs[*index], tower) <= RANGE)
+                        .min()
+                })
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, targeting);
+criterion_main!(benches);
@@ -0,0 +1,292 @@
+use std::io::Write;
+
+use ggez::event::{KeyCode, MouseButton};
+use ggez::Context;
+use ggez_goodies::input::InputEffect;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::input::{Axis, Button};
+use crate::types::Error;
+
+/// Kept in the user config directory, next to the settings.
+const BINDINGS_PATH: &str = "/bindings.ron";
+
+/// Something an input can be bound to, either one direction of an axis or a button.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Axis(Axis, bool),
+    Button(Button),
+}
+
+/// Every action that can be rebound, in the order the controls scene lists them.
+pub const ACTIONS: [Action; 19] = [
+    Action::Axis(Axis::Vert, true),
+    Action::Axis(Axis::Vert, false),
+    Action::Axis(Axis::Horz, false),
+    Action::Axis(Axis::Horz, true),
+    Action::Button(Button::Select),
+    Action::Button(Button::Cancel),
+    Action::Button(Button::Sell),
+    Action::Button(Button::Move),
+    Action::Button(Button::Merge),
+    Action::Button(Button::StartRound),
+    Action::Button(Button::SpeedUp),
+    Action::Button(Button::CycleTargeting),
+    Action::Button(Button::Pan),
+    Action::Button(Button::Recipes),
+    Action::Button(Button::Paths),
+    Action::Button(Button::Debug),
+    Action::Button(Button::Navigation),
+    Action::Button(Button::Menu),
+    Action::Button(Button::Quit),
+];
+
+impl Action {
+    pub fn effect(self) -> InputEffect<Axis, Button> {
+        match self {
+            Action::Axis(axis, positive) => InputEffect::Axis(axis, positive),
+            Action::Button(button) => InputEffect::Button(button),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Action::Axis(Axis::Vert, true) => "Up".to_owned(),
+            Action::Axis(Axis::Vert, false) => "Down".to_owned(),
+            Action::Axis(Axis::Horz, false) => "Left".to_owned(),
+            Action::Axis(Axis::Horz, true) => "Right".to_owned(),
+            Action::Button(button) => format!("{:?}", button),
+        }
+    }
+}
+
+/// A physical key or mouse button.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoundInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+macro_rules! key_names {
+    ($($key:ident),* $(,)*) => {
+        /// Keys that can be bound, with the names they're saved under.
+        const KEY_NAMES: &[(KeyCode, &str)] = &[$((KeyCode::$key, stringify!($key))),*];
+    };
+}
+
+key_names!(
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J, K, L,
+    M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Escape, Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down, Back, Return,
+    Space, Tab, LShift, RShift, LControl, RControl, LAlt, RAlt, Minus, Equals, Comma, Period,
+    Slash, Semicolon, LBracket, RBracket, Grave, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4,
+    Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+);
+
+const MOUSE_NAMES: [(MouseButton, &str); 3] = [
+    (MouseButton::Left, "MouseLeft"),
+    (MouseButton::Right, "MouseRight"),
+    (MouseButton::Middle, "MouseMiddle"),
+];
+
+impl BoundInput {
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            BoundInput::Key(key) => KEY_NAMES
+                .iter()
+                .find(|(other, _)| *other == key)
+                .map(|(_, name)| *name),
+            BoundInput::Mouse(button) => MOUSE_NAMES
+                .iter()
+                .find(|(other, _)| *other == button)
+                .map(|(_, name)| *name),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        KEY_NAMES
+            .iter()
+            .find(|(_, other)| *other == name)
+            .map(|(key, _)| BoundInput::Key(*key))
+            .or_else(|| {
+                MOUSE_NAMES
+                    .iter()
+                    .find(|(_, other)| *other == name)
+                    .map(|(button, _)| BoundInput::Mouse(*button))
+            })
+    }
+}
+
+/// The on-disk form, since ggez's key codes can't be serialized themselves.
+#[derive(Debug, Serialize, Deserialize)]
+struct BindingsFile {
+    bindings: Vec<(String, Action)>,
+}
+
+/// Maps keys and mouse buttons to actions. Each input triggers at most one action,
+/// while an action may have several inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    bindings: Vec<(BoundInput, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let key = |key, action| (BoundInput::Key(key), action);
+        let mouse = |button, action| (BoundInput::Mouse(button), action);
+
+        let bindings = vec![
+            key(KeyCode::Up, Action::Axis(Axis::Vert, true)),
+            key(KeyCode::Down, Action::Axis(Axis::Vert, false)),
+            key(KeyCode::Left, Action::Axis(Axis::Horz, false)),
+            key(KeyCode::Right, Action::Axis(Axis::Horz, true)),
+            key(KeyCode::Return, Action::Button(Button::Select)),
+            mouse(MouseButton::Left, Action::Button(Button::Select)),
+            key(KeyCode::Back, Action::Button(Button::Cancel)),
+            mouse(MouseButton::Right, Action::Button(Button::Cancel)),
+            key(KeyCode::X, Action::Button(Button::Sell)),
+            key(KeyCode::C, Action::Button(Button::Move)),
+            key(KeyCode::V, Action::Button(Button::Merge)),
+            key(KeyCode::Space, Action::Button(Button::StartRound)),
+            key(KeyCode::F, Action::Button(Button::SpeedUp)),
+            key(KeyCode::T, Action::Button(Button::CycleTargeting)),
+            mouse(MouseButton::Middle, Action::Button(Button::Pan)),
+            key(KeyCode::R, Action::Button(Button::Recipes)),
+            key(KeyCode::P, Action::Button(Button::Paths)),
+            key(KeyCode::F1, Action::Button(Button::Debug)),
+            key(KeyCode::F2, Action::Button(Button::Navigation)),
+            key(KeyCode::Z, Action::Button(Button::Menu)),
+            key(KeyCode::Escape, Action::Button(Button::Quit)),
+        ];
+
+        Bindings { bindings }
+    }
+}
+
+impl Bindings {
+    /// Loads the saved bindings, falling back to the defaults if there are none yet.
+    pub fn load(ctx: &mut Context) -> Result<Self, Error> {
+        if !ggez::filesystem::exists(ctx, BINDINGS_PATH) {
+            return Ok(Bindings::default());
+        }
+
+        let file = ggez::filesystem::open(ctx, BINDINGS_PATH).map_err(Error::GgezError)?;
+        let file: BindingsFile = ron::de::from_reader(file).map_err(Error::DeserializeError)?;
+
+        Ok(Bindings::from_file(file))
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> Result<(), Error> {
+        let ron = ron::ser::to_string_pretty(&self.to_file(), ron::ser::PrettyConfig::default())
+            .map_err(Error::SerializeError)?;
+        let mut file = ggez::filesystem::create(ctx, BINDINGS_PATH).map_err(Error::GgezError)?;
+
+        file.write_all(ron.as_bytes()).map_err(Error::IoError)
+    }
+
+    fn from_file(file: BindingsFile) -> Self {
+        let bindings = file
+            .bindings
+            .into_iter()
+            .filter_map(|(name, action)| match BoundInput::from_name(&name) {
+                Some(input) => Some((input, action)),
+                None => {
+                    warn!("Ignoring binding for unknown input {:?}", name);
+                    None
+                }
+            })
+            .collect();
+
+        Bindings { bindings }
+    }
+
+    fn to_file(&self) -> BindingsFile {
+        BindingsFile {
+            bindings: self
+                .bindings
+                .iter()
+                .filter_map(|(input, action)| input.name().map(|name| (name.to_owned(), *action)))
+                .collect(),
+        }
+    }
+
+    pub fn resolve(&self, input: BoundInput) -> Option<InputEffect<Axis, Button>> {
+        self.bindings
+            .iter()
+            .find(|(other, _)| *other == input)
+            .map(|(_, action)| action.effect())
+    }
+
+    pub fn resolve_key(&self, key: KeyCode) -> Option<InputEffect<Axis, Button>> {
+        self.resolve(BoundInput::Key(key))
+    }
+
+    pub fn resolve_mouse(&self, button: MouseButton) -> Option<InputEffect<Axis, Button>> {
+        self.resolve(BoundInput::Mouse(button))
+    }
+
+    pub fn inputs_for(&self, action: Action) -> Vec<BoundInput> {
+        self.bindings
+            .iter()
+            .filter(|(_, other)| *other == action)
+            .map(|(input, _)| *input)
+            .collect()
+    }
+
+    /// Makes `input` the only binding of `action`, taking it away from whatever it was bound to.
+    pub fn rebind(&mut self, action: Action, input: BoundInput) {
+        self.bindings
+            .retain(|(other_input, other_action)| *other_input != input && *other_action != action);
+        self.bindings.push((input, action));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for input in &[
+            BoundInput::Key(KeyCode::Z),
+            BoundInput::Key(KeyCode::Key1),
+            BoundInput::Mouse(MouseButton::Middle),
+        ] {
+            assert_eq!(Some(*input), BoundInput::from_name(input.name().unwrap()));
+        }
+
+        assert_eq!(None, BoundInput::from_name("NotAKey"));
+    }
+
+    #[test]
+    fn rebind_replaces_both_sides() {
+        let mut bindings = Bindings::default();
+        let menu = Action::Button(Button::Menu);
+
+        bindings.rebind(menu, BoundInput::Key(KeyCode::X));
+
+        assert_eq!(vec![BoundInput::Key(KeyCode::X)], bindings.inputs_for(menu));
+        assert!(bindings.inputs_for(Action::Button(Button::Sell)).is_empty());
+        assert_eq!(None, bindings.resolve_key(KeyCode::Z));
+    }
+
+    #[test]
+    fn file_round_trip_skips_unknown_inputs() {
+        let file: BindingsFile = ron::de::from_str(
+            "(bindings: [(\"Q\", Button(Quit)), (\"Nope\", Button(Sell)), (\"MouseRight\", Axis(Vert, true))])",
+        )
+        .unwrap();
+        let bindings = Bindings::from_file(file);
+
+        assert_eq!(
+            Some(InputEffect::Button(Button::Quit)),
+            bindings.resolve_key(KeyCode::Q)
+        );
+        assert_eq!(
+            Some(InputEffect::Axis(Axis::Vert, true)),
+            bindings.resolve_mouse(MouseButton::Right)
+        );
+        assert_eq!(2, bindings.to_file().bindings.len());
+    }
+}
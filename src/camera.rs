@@ -0,0 +1,157 @@
+use ggez::graphics;
+use ggez::nalgebra as na;
+
+use crate::screen::Transform;
+
+pub const MIN_ZOOM: f32 = 0.5;
+pub const MAX_ZOOM: f32 = 3.0;
+
+/// Looks at the world pixels within `bounds`. `position` is the world point shown at the
+/// center of the viewport.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub position: na::Point2<f32>,
+    pub zoom: f32,
+    /// The scale at zoom 1, normally the UI scale from the settings.
+    base_scale: f32,
+    viewport: na::Vector2<f32>,
+    bounds_origin: na::Point2<f32>,
+    bounds_size: na::Vector2<f32>,
+}
+
+impl Camera {
+    pub fn new(bounds: graphics::Rect, viewport: na::Vector2<f32>, base_scale: f32) -> Self {
+        let bounds_origin = na::Point2::new(bounds.x, bounds.y);
+        let bounds_size = na::Vector2::new(bounds.w, bounds.h);
+
+        Camera {
+            position: bounds_origin + bounds_size / 2.0,
+            zoom: 1.0,
+            base_scale,
+            viewport,
+            bounds_origin,
+            bounds_size,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.base_scale * self.zoom
+    }
+
+    pub fn set_viewport(&mut self, viewport: na::Vector2<f32>, base_scale: f32) {
+        self.viewport = viewport;
+        self.base_scale = base_scale;
+        self.clamp();
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform {
+            origin: na::Point2::from(self.viewport / 2.0 - self.position.coords * self.scale()),
+            scale: self.scale(),
+        }
+    }
+
+    pub fn to_screen(&self, point: &na::Point2<f32>) -> na::Point2<f32> {
+        self.transform().world_to_screen(point)
+    }
+
+    pub fn to_world(&self, point: &na::Point2<f32>) -> na::Point2<f32> {
+        self.transform().screen_to_world(point)
+    }
+
+    /// Moves the view by a distance given in screen pixels.
+    pub fn pan(&mut self, screen_delta: na::Vector2<f32>) {
+        self.position += screen_delta / self.scale();
+        self.clamp();
+    }
+
+    /// Zooms by `factor` while keeping the world point under `screen_point` in place.
+    pub fn zoom_at(&mut self, screen_point: &na::Point2<f32>, factor: f32) {
+        let anchor = self.to_world(screen_point);
+
+        self.zoom = na::clamp(self.zoom * factor, MIN_ZOOM, MAX_ZOOM);
+        self.position += anchor - self.to_world(screen_point);
+        self.clamp();
+    }
+
+    /// Keeps the view within the world, or centered on it when the world fits on screen.
+    fn clamp(&mut self) {
+        let half_view = self.viewport / (2.0 * self.scale());
+
+        for axis in 0..2 {
+            let min = self.bounds_origin[axis];
+            let size = self.bounds_size[axis];
+
+            self.position[axis] = if half_view[axis] * 2.0 >= size {
+                min + size / 2.0
+            } else {
+                na::clamp(
+                    self.position[axis],
+                    min + half_view[axis],
+                    min + size - half_view[axis],
+                )
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_camera() -> Camera {
+        Camera::new(
+            graphics::Rect::new(0.0, 0.0, 1000.0, 1000.0),
+            na::Vector2::new(400.0, 300.0),
+            2.0,
+        )
+    }
+
+    #[test]
+    fn screen_and_world_round_trip() {
+        let mut camera = make_camera();
+        camera.pan(na::Vector2::new(-120.0, 80.0));
+
+        let point = na::Point2::new(123.0, 45.0);
+        let round_trip = camera.to_screen(&camera.to_world(&point));
+
+        assert!(na::distance(&point, &round_trip) < 0.001);
+    }
+
+    #[test]
+    fn pan_is_clamped_to_bounds() {
+        let mut camera = make_camera();
+        camera.pan(na::Vector2::new(-100000.0, 100000.0));
+
+        assert_eq!(
+            na::Point2::new(0.0, 300.0),
+            camera.to_screen(&na::Point2::new(0.0, 1000.0))
+        );
+    }
+
+    #[test]
+    fn zoom_keeps_anchor_in_place() {
+        let mut camera = make_camera();
+        let anchor = na::Point2::new(250.0, 100.0);
+        let world = camera.to_world(&anchor);
+
+        camera.zoom_at(&anchor, 1.5);
+
+        assert_eq!(1.5, camera.zoom);
+        assert!(na::distance(&world, &camera.to_world(&anchor)) < 0.001);
+    }
+
+    #[test]
+    fn small_world_is_centered() {
+        let mut camera = Camera::new(
+            graphics::Rect::new(-20.0, -20.0, 300.0, 200.0),
+            na::Vector2::new(400.0, 300.0),
+            2.0,
+        );
+        camera.zoom_at(&na::Point2::new(0.0, 0.0), 0.1);
+        camera.pan(na::Vector2::new(500.0, 0.0));
+
+        assert_eq!(MIN_ZOOM, camera.zoom);
+        assert_eq!(na::Point2::new(130.0, 80.0), camera.position);
+    }
+}
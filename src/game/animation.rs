@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use ggez::nalgebra as na;
+use serde::Deserialize;
+
+/// Frame duration used for the `sprite_frames` of definitions without animations.
+pub const DEFAULT_FRAME_DURATION: f32 = 0.2;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationState {
+    Idle,
+    Walk,
+    Attack,
+    Death,
+}
+
+impl Default for AnimationState {
+    fn default() -> Self {
+        AnimationState::Idle
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Down,
+    Up,
+    Left,
+    Right,
+}
+
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::Down
+    }
+}
+
+impl Direction {
+    /// The direction closest to `vector`, in screen space where y points down.
+    pub fn from_vector(vector: &na::Vector2<f32>) -> Self {
+        if vector.x.abs() > vector.y.abs() {
+            if vector.x < 0.0 {
+                Direction::Left
+            } else {
+                Direction::Right
+            }
+        } else if vector.y < 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum LoopMode {
+    Loop,
+    /// Stops on the last frame.
+    Once,
+    /// Plays forwards, then backwards, over and over.
+    PingPong,
+}
+
+impl Default for LoopMode {
+    fn default() -> Self {
+        LoopMode::Loop
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub struct Frame {
+    pub sprite_id: i32,
+    /// Seconds the frame is shown for.
+    pub duration: f32,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    #[serde(default)]
+    pub loop_mode: LoopMode,
+    /// Frames to use instead of `frames` when facing a given direction.
+    #[serde(default)]
+    pub directions: HashMap<Direction, Vec<Frame>>,
+}
+
+impl Animation {
+    /// Loops through the given sprites, each shown for the same duration.
+    pub fn from_sprites(sprites: &[i32], duration: f32) -> Self {
+        Animation {
+            frames: sprites
+                .iter()
+                .map(|&sprite_id| Frame {
+                    sprite_id,
+                    duration,
+                })
+                .collect(),
+            loop_mode: LoopMode::Loop,
+            directions: HashMap::new(),
+        }
+    }
+
+    fn frames(&self, direction: Direction) -> &[Frame] {
+        self.directions
+            .get(&direction)
+            .map_or(&self.frames, |frames| frames)
+    }
+
+    pub fn duration(&self, direction: Direction) -> f32 {
+        self.frames(direction)
+            .iter()
+            .map(|frame| frame.duration)
+            .sum()
+    }
+
+    pub fn is_finished(&self, direction: Direction, elapsed: f32) -> bool {
+        self.loop_mode == LoopMode::Once && elapsed >= self.duration(direction)
+    }
+
+    /// The sprite to show `elapsed` seconds into the animation.
+    pub fn sprite_at(&self, direction: Direction, elapsed: f32) -> Option<i32> {
+        let frames = self.frames(direction);
+        let total = self.duration(direction);
+
+        if total <= 0.0 {
+            return frames.first().map(|frame| frame.sprite_id);
+        }
+
+        let mut time = match self.loop_mode {
+            LoopMode::Loop => elapsed % total,
+            LoopMode::Once => elapsed.min(total),
+            LoopMode::PingPong => {
+                let time = elapsed % (total * 2.0);
+
+                if time < total {
+                    time
+                } else {
+                    total * 2.0 - time
+                }
+            }
+        };
+
+        for frame in frames {
+            if time < frame.duration {
+                return Some(frame.sprite_id);
+            }
+
+            time -= frame.duration;
+        }
+
+        frames.last().map(|frame| frame.sprite_id)
+    }
+}
+
+/// The animations of a unit or mob, by the state they are shown in.
+pub type AnimationSet = HashMap<AnimationState, Animation>;
+
+/// Tracks which animation a unit or mob is playing and how far along it is.
+#[derive(Debug, Clone, Default)]
+pub struct Animator {
+    state: AnimationState,
+    direction: Direction,
+    elapsed: f32,
+}
+
+impl Animator {
+    pub fn state(&self) -> AnimationState {
+        self.state
+    }
+
+    /// Switches to `state`, keeping the current animation going if it is already playing.
+    pub fn set_state(&mut self, state: AnimationState) {
+        if self.state != state {
+            self.play(state);
+        }
+    }
+
+    /// Starts `state` from its first frame.
+    pub fn play(&mut self, state: AnimationState) {
+        self.state = state;
+        self.elapsed = 0.0;
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+
+    /// Whether a non looping animation has played through, or there is none to play.
+    pub fn is_finished(&self, animations: &AnimationSet) -> bool {
+        animations.get(&self.state).map_or(true, |animation| {
+            animation.is_finished(self.direction, self.elapsed)
+        })
+    }
+
+    /// The sprite for the current frame, falling back to the idle animation
+    /// for states the set has no animation for.
+    pub fn sprite_id(&self, animations: &AnimationSet) -> Option<i32> {
+        match animations.get(&self.state) {
+            Some(animation) => animation.sprite_at(self.direction, self.elapsed),
+            None => animations
+                .get(&AnimationState::Idle)
+                .and_then(|animation| animation.sprite_at(self.direction, self.elapsed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(sprites: &[i32]) -> Vec<Frame> {
+        sprites
+            .iter()
+            .map(|&sprite_id| Frame {
+                sprite_id,
+                duration: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_modes_pick_frames() {
+        let mut animation = Animation::from_sprites(&[1, 2, 3], 1.0);
+
+        assert_eq!(Some(1), animation.sprite_at(Direction::Down, 0.5));
+        assert_eq!(Some(3), animation.sprite_at(Direction::Down, 2.5));
+        assert_eq!(Some(1), animation.sprite_at(Direction::Down, 3.5));
+
+        animation.loop_mode = LoopMode::Once;
+        assert_eq!(Some(3), animation.sprite_at(Direction::Down, 10.0));
+        assert!(animation.is_finished(Direction::Down, 3.0));
+
+        animation.loop_mode = LoopMode::PingPong;
+        assert_eq!(Some(3), animation.sprite_at(Direction::Down, 3.5));
+        assert_eq!(Some(2), animation.sprite_at(Direction::Down, 4.5));
+        assert_eq!(Some(1), animation.sprite_at(Direction::Down, 5.5));
+    }
+
+    #[test]
+    fn directions_override_frames() {
+        let mut animation = Animation::from_sprites(&[1], 1.0);
+        animation
+            .directions
+            .insert(Direction::Left, frames(&[7, 8]));
+
+        assert_eq!(Some(1), animation.sprite_at(Direction::Right, 1.5));
+        assert_eq!(Some(8), animation.sprite_at(Direction::Left, 1.5));
+        assert_eq!(
+            Direction::Left,
+            Direction::from_vector(&na::Vector2::new(-2.0, 1.0))
+        );
+        assert_eq!(
+            Direction::Up,
+            Direction::from_vector(&na::Vector2::new(0.5, -1.0))
+        );
+    }
+
+    #[test]
+    fn animator_falls_back_to_idle() {
+        let mut animations = AnimationSet::new();
+        animations.insert(AnimationState::Idle, Animation::from_sprites(&[4], 1.0));
+
+        let mut animator = Animator::default();
+        animator.set_state(AnimationState::Walk);
+        animator.update(0.5);
+
+        assert_eq!(Some(4), animator.sprite_id(&animations));
+        assert!(animator.is_finished(&animations));
+        assert_eq!(None, animator.sprite_id(&AnimationSet::new()));
+    }
+}
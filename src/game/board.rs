@@ -1,45 +1,85 @@
+use std::cmp::Reverse;
+
 use ggez::nalgebra as na;
 
+use log::*;
 use pathfinding::prelude::astar;
 
-use crate::game::mob::MobEntity;
-use crate::game::unit::Unit;
+use crate::game::animation::Direction;
+use crate::game::events::CombatEvent;
+use crate::game::mob::{AbilityEffect, MobDefinition, MobEntity, MobEntityStatus};
+use crate::game::navigation::FlowField;
+use crate::game::registry::MobRegistry;
+use crate::game::spatial::SpatialGrid;
+use crate::game::unit::{DamageType, TargetPriority, Targets, Unit};
+use crate::screen::{Transform, TILE_SIZE};
 
 pub struct Board {
     pub tiles: Vec<Unit>,
     pub mobs: Vec<MobEntity>,
+    /// Killed mobs playing their death animation, out of reach of units.
+    pub dying: Vec<MobEntity>,
+    pub gold: u32,
+    pub lives: u32,
+    /// Minions requested by boss abilities, spawned by the scene from the mob registry.
+    pub pending_spawns: Vec<SpawnRequest>,
+    /// What happened during the last updates, drained by the scene.
+    pub events: Vec<CombatEvent>,
+
+    /// The round being fought and how many spawns it has left, until it is cleared.
+    wave: Option<Wave>,
+
+    mob_grid: SpatialGrid,
+    /// Ids are handed out in spawn order and never reused.
+    next_mob_id: u64,
 
     waypoints: Vec<(na::Point2<i32>, na::Point2<i32>)>,
 }
 
-const BOARD_HEIGHT: usize = 40;
-const BOARD_WIDTH: usize = 40;
+pub const BOARD_HEIGHT: usize = 40;
+pub const BOARD_WIDTH: usize = 40;
 const BOARD_SIZE: usize = BOARD_HEIGHT * BOARD_WIDTH;
 
+/// Percentage of a unit's cost given back when it is sold.
+const SELL_REFUND_PERCENTAGE: u32 = 75;
+
+const STARTING_LIVES: u32 = 20;
+
+/// Cell size of the spatial grid used for range queries against mobs, in pixels.
+const MOB_GRID_CELL_SIZE: f32 = 32.0;
+
+#[derive(Debug, Clone)]
+pub struct SpawnRequest {
+    pub mob_id: String,
+    pub count: u32,
+    pub position: na::Point2<f32>,
+    pub destination: na::Point2<i32>,
+    pub path_index: u32,
+    pub lane: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Wave {
+    round: u32,
+    spawns_left: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PlacementError {
+    Occupied,
+    Empty,
+    BlocksPath,
+}
+
 #[derive(Debug)]
 struct DamageEvent {
     damage: u32,
+    damage_type: DamageType,
     unit_position: na::Point2<i32>,
     source: na::Point2<f32>,
     range: f32,
-    applied: bool,
-}
-
-impl DamageEvent {
-    pub fn new(
-        damage: u32,
-        unit_position: na::Point2<i32>,
-        source: na::Point2<f32>,
-        range: f32,
-    ) -> Self {
-        Self {
-            damage,
-            unit_position,
-            source,
-            range,
-            applied: false,
-        }
-    }
+    targets: Targets,
+    priority: TargetPriority,
 }
 
 impl Board {
@@ -55,6 +95,170 @@ impl Board {
         })
     }
 
+    fn index_at(&self, coordinates: &na::Point2<i32>) -> Option<usize> {
+        self.tiles.iter().position(|unit| {
+            unit.tile_position.x == coordinates.x && unit.tile_position.y == coordinates.y
+        })
+    }
+
+    /// Places a unit on its `tile_position`, as long as the tile is free and
+    /// every waypoint can still be reached afterwards.
+    pub fn place(&mut self, unit: Unit) -> Result<(), PlacementError> {
+        self.preview_placement(&unit.tile_position)?;
+        self.tiles.push(unit);
+
+        Ok(())
+    }
+
+    /// The paths mobs would take with a unit on `position`, leaving the board as it is.
+    pub fn preview_placement(
+        &mut self,
+        position: &na::Point2<i32>,
+    ) -> Result<Vec<na::Point2<i32>>, PlacementError> {
+        if self.at_position(position).is_some() {
+            return Err(PlacementError::Occupied);
+        }
+
+        self.tiles.push(Unit {
+            tile_position: *position,
+            ..Unit::default()
+        });
+
+        let paths = self.calculate_paths();
+
+        self.tiles.pop();
+
+        paths.ok_or(PlacementError::BlocksPath)
+    }
+
+    /// Removes the unit at the given position, refunding a percentage of its cost.
+    pub fn sell(&mut self, coordinates: &na::Point2<i32>) -> Result<u32, PlacementError> {
+        let index = self.index_at(coordinates).ok_or(PlacementError::Empty)?;
+        let unit = self.tiles.remove(index);
+        let refund = unit.cost * SELL_REFUND_PERCENTAGE / 100;
+
+        self.gold += refund;
+
+        Ok(refund)
+    }
+
+    /// Moves the unit at `from` to `to`. If `to` holds another unit, the two
+    /// units swap places, otherwise the move is rejected if it would block the path.
+    pub fn relocate(
+        &mut self,
+        from: &na::Point2<i32>,
+        to: &na::Point2<i32>,
+    ) -> Result<(), PlacementError> {
+        let from_index = self.index_at(from).ok_or(PlacementError::Empty)?;
+
+        match self.index_at(to) {
+            Some(to_index) => {
+                self.tiles[from_index].tile_position = *to;
+                self.tiles[to_index].tile_position = *from;
+            }
+            None => {
+                self.tiles[from_index].tile_position = *to;
+
+                if self.calculate_paths().is_none() {
+                    self.tiles[from_index].tile_position = *from;
+                    return Err(PlacementError::BlocksPath);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The tile mobs spawn on, which is the start of the first waypoint.
+    pub fn spawn_point(&self) -> na::Point2<i32> {
+        self.waypoints
+            .first()
+            .map(|(start, _)| *start)
+            .unwrap_or_else(|| na::Point2::new(0, 0))
+    }
+
+    /// Starts fighting `round`, cleared once all its `spawns` are out and every mob is gone.
+    pub fn start_wave(&mut self, round: u32, spawns: u32) {
+        self.wave = Some(Wave {
+            round,
+            spawns_left: spawns,
+        });
+    }
+
+    pub fn wave_in_progress(&self) -> bool {
+        self.wave.is_some()
+    }
+
+    /// Spawns one of the wave's spawns, which can be any number of mobs.
+    pub fn spawn_wave(&mut self, definitions: &[&MobDefinition]) {
+        let spawn_point = self.spawn_point();
+
+        for definition in definitions {
+            self.mobs.push(MobEntity::new(self.next_mob_id, definition, &spawn_point));
+            self.next_mob_id += 1;
+        }
+
+        if let Some(wave) = self.wave.as_mut() {
+            wave.spawns_left = wave.spawns_left.saturating_sub(1);
+        }
+    }
+
+    /// Removes mobs that walked off the end of the path, costing lives.
+    pub fn remove_leaked_mobs(&mut self) {
+        let mut leaked = 0;
+
+        for mob in self
+            .mobs
+            .iter()
+            .filter(|mob| mob.status == MobEntityStatus::FinishedPath)
+        {
+            leaked += mob.life_cost;
+            self.events.push(CombatEvent::MobLeaked {
+                mob: mob.id,
+                position: mob.position,
+                life_cost: mob.life_cost,
+            });
+        }
+
+        self.lives = self.lives.saturating_sub(leaked);
+        self.mobs
+            .retain(|mob| mob.status != MobEntityStatus::FinishedPath);
+    }
+
+    /// Spawns minions requested by boss abilities at the position of the mob that summoned them.
+    pub fn spawn_pending(&mut self, registry: &MobRegistry) {
+        for request in self.pending_spawns.drain(..) {
+            match registry.get(&request.mob_id) {
+                Some(definition) => {
+                    for _ in 0..request.count {
+                        let mut minion =
+                            MobEntity::new(self.next_mob_id, definition, &na::Point2::new(0, 0));
+                        self.next_mob_id += 1;
+
+                        minion.position = request.position;
+                        minion.last_position = request.position;
+                        minion.destination = request.destination;
+                        minion.path_index = request.path_index;
+                        minion.lane = request.lane;
+
+                        self.mobs.push(minion);
+                    }
+                }
+                None => warn!("Unknown minion {:?}", request.mob_id),
+            }
+        }
+    }
+
+    /// The waypoints themselves, which flying mobs travel between in straight lines.
+    pub fn checkpoints(&self) -> Vec<na::Point2<i32>> {
+        self.waypoints
+            .first()
+            .map(|(start, _)| *start)
+            .into_iter()
+            .chain(self.waypoints.iter().map(|(_, end)| *end))
+            .collect()
+    }
+
     pub fn calculate_path(
         &self,
         from: &na::Point2<i32>,
@@ -68,25 +272,95 @@ impl Board {
         )
     }
 
-    pub fn calculate_paths(&self) -> Option<(Vec<na::Point2<i32>>)> {
-        let results = self
-            .waypoints
+    /// The path between each pair of waypoints, or none if any of them is blocked.
+    pub fn calculate_lane_paths(&self) -> Option<Vec<Vec<na::Point2<i32>>>> {
+        self.waypoints
             .iter()
-            .map(|(start, end)| self.calculate_path(start, end));
+            .map(|(start, end)| self.calculate_path(start, end).map(|(points, _)| points))
+            .collect()
+    }
+
+    pub fn calculate_paths(&self) -> Option<(Vec<na::Point2<i32>>)> {
+        self.calculate_lane_paths().map(|lanes| lanes.concat())
+    }
+
+    fn resolve_mob_abilities(&mut self) {
+        let mut effects = Vec::new();
+
+        for mob in self.mobs.iter_mut() {
+            for effect in mob.triggered_effects.drain(..) {
+                effects.push((
+                    effect,
+                    mob.position,
+                    mob.destination,
+                    mob.path_index,
+                    mob.lane,
+                ));
+            }
+        }
+
+        for (effect, position, destination, path_index, lane) in effects {
+            match effect {
+                AbilityEffect::SpawnMinions { mob_id, count } => {
+                    self.pending_spawns.push(SpawnRequest {
+                        mob_id,
+                        count,
+                        position,
+                        destination,
+                        path_index,
+                        lane,
+                    });
+                }
+                AbilityEffect::HealNearby { radius, amount } => {
+                    for index in self.mob_grid.query(&position, radius) {
+                        let mob = &mut self.mobs[index];
+
+                        if na::distance(&mob.position, &position) <= radius {
+                            mob.heal(amount);
+                        }
+                    }
+                }
+                AbilityEffect::DisableUnits { radius, duration } => {
+                    for unit in self.tiles.iter_mut() {
+                        let unit_position = Transform::tile_center(&unit.tile_position);
 
-        if results.clone().any(|result| result.is_none()) {
-            None
-        } else {
-            Some(results.fold(Vec::with_capacity(32), |mut acc, result| {
-                let (mut points, _) = result.unwrap();
-                acc.append(&mut points);
-                acc
-            }))
+                        if na::distance(&unit_position, &position) <= radius {
+                            unit.disable(duration);
+                        }
+                    }
+                }
+                AbilityEffect::Invulnerable { .. } | AbilityEffect::SpeedBurst { .. } => {}
+            }
         }
     }
 
-    pub fn update(&mut self) {
-        let mut damage_events: Vec<DamageEvent> = self
+    /// One flow field per waypoint, each flowing towards that waypoint's goal.
+    pub fn calculate_flow_fields(&self) -> Vec<FlowField> {
+        self.waypoints
+            .iter()
+            .map(|(_, goal)| {
+                FlowField::new(BOARD_WIDTH, BOARD_HEIGHT, *goal, |point| {
+                    self.successors(point)
+                        .into_iter()
+                        .map(|(neighbour, _)| neighbour)
+                        .collect()
+                })
+            })
+            .collect()
+    }
+
+    /// Runs one tick of combat, `dt` seconds of game time long.
+    pub fn update(&mut self, dt: f32) {
+        for unit in self.tiles.iter_mut() {
+            unit.tick(dt);
+        }
+
+        self.mob_grid
+            .rebuild(self.mobs.iter().map(|mob| mob.position));
+
+        self.resolve_mob_abilities();
+
+        let damage_events: Vec<DamageEvent> = self
             .tiles
             .iter_mut()
             .filter_map(|unit| {
@@ -94,55 +368,134 @@ impl Board {
 
                 match unit.check_attack() {
                     Some(damage) => {
-                        let real_position = na::Point2::new(
-                            (position.x as f32 * 16.0) + 4.0,
-                            (position.y as f32 * 16.0) + 4.0,
-                        );
+                        let real_position = Transform::tile_center(&position);
 
-                        Some(DamageEvent::new(
+                        Some(DamageEvent {
                             damage,
-                            unit.tile_position,
-                            real_position,
-                            unit.range,
-                        ))
+                            damage_type: unit.damage_type,
+                            unit_position: unit.tile_position,
+                            source: real_position,
+                            range: unit.range,
+                            targets: unit.targets,
+                            priority: unit.target_priority,
+                        })
                     }
                     _ => None,
                 }
             })
             .collect();
 
-        for damage_event in damage_events.iter_mut() {
-            let mut index = 0;
+        // Mobs can be hit from anywhere within their own radius, so widen the grid query by
+        // the largest one. Dead mobs are only removed at the end, keeping grid indices valid.
+        let max_mob_radius = self
+            .mobs
+            .iter()
+            .map(|mob| mob.size / 2.0)
+            .fold(0.0, f32::max);
 
-            while index != self.mobs.len() {
-                let mob = &mut self.mobs[index];
+        for damage_event in damage_events.iter() {
+            let mobs = &self.mobs;
 
-                if damage_event.applied {
-                    break;
-                } else {
+            let candidates = self
+                .mob_grid
+                .query(&damage_event.source, damage_event.range + max_mob_radius)
+                .filter(|index| {
+                    let mob = &mobs[*index];
                     let distance = na::distance(&mob.position, &damage_event.source);
 
-                    if distance <= damage_event.range {
-                        mob.damage(damage_event.damage);
-                        damage_event.applied = true;
+                    mob.is_alive()
+                        && distance <= damage_event.range + mob.size / 2.0
+                        && damage_event.targets.can_hit(mob.movement)
+                });
 
-                        if mob.is_alive() {
-                            index += 1;
-                        } else {
-                            self.mobs.remove(index);
-                        }
-                    } else {
-                        index += 1;
-                    }
+            let target = match damage_event.priority {
+                TargetPriority::First => candidates.max_by(|a, b| {
+                    mobs[*a].compare_progress(&mobs[*b]).then(b.cmp(a))
+                }),
+                TargetPriority::Last => candidates.min_by(|a, b| {
+                    mobs[*a].compare_progress(&mobs[*b]).then(a.cmp(b))
+                }),
+                TargetPriority::Strongest => {
+                    candidates.max_by_key(|index| (mobs[*index].current_health, Reverse(*index)))
+                }
+                TargetPriority::Weakest => {
+                    candidates.min_by_key(|index| (mobs[*index].current_health, *index))
+                }
+            };
+
+            if let Some(index) = target {
+                let mob = &mut self.mobs[index];
+                let target_position = mob.position;
+                let health_before = mob.current_health;
+
+                mob.damage(damage_event.damage);
+
+                let dealt = (health_before - mob.current_health.max(0)) as u32;
+                let killed = !mob.is_alive();
+
+                self.events.push(CombatEvent::UnitAttacked {
+                    unit: damage_event.unit_position,
+                    mob: mob.id,
+                });
+                self.events.push(CombatEvent::MobDamaged {
+                    mob: mob.id,
+                    position: mob.position,
+                    damage: dealt,
+                    damage_type: damage_event.damage_type,
+                });
+
+                if killed {
+                    self.gold += mob.bounty;
+                    self.events.push(CombatEvent::MobKilled {
+                        mob: mob.id,
+                        position: mob.position,
+                        bounty: mob.bounty,
+                    });
                 }
-            }
 
-            if damage_event.applied {
                 if let Some(unit) = self.at_position_mut(&damage_event.unit_position) {
+                    unit.animator.set_direction(Direction::from_vector(
+                        &(target_position - damage_event.source),
+                    ));
                     unit.perform_attack();
+                    unit.record_hit(dealt, killed);
                 }
             }
         }
+
+        let (alive, dead): (Vec<MobEntity>, Vec<MobEntity>) =
+            self.mobs.drain(..).partition(|mob| mob.is_alive());
+
+        self.mobs = alive;
+
+        for mut mob in dead {
+            mob.die();
+            self.dying.push(mob);
+        }
+
+        self.check_wave_cleared();
+    }
+
+    fn check_wave_cleared(&mut self) {
+        if let Some(wave) = self.wave {
+            if wave.spawns_left == 0 && self.mobs.is_empty() && self.pending_spawns.is_empty() {
+                self.events.push(CombatEvent::WaveCleared { round: wave.round });
+                self.wave = None;
+            }
+        }
+    }
+
+    /// Advances unit and death animations, removing mobs whose death has played out.
+    pub fn update_animations(&mut self, dt: f32) {
+        for unit in self.tiles.iter_mut() {
+            unit.update_animation(dt);
+        }
+
+        for mob in self.dying.iter_mut() {
+            mob.animator.update(dt);
+        }
+
+        self.dying.retain(|mob| !mob.death_finished());
     }
 
     // @TODO(vy): This should handle the cases of preventing movement through diagonals.
@@ -170,6 +523,7 @@ impl Board {
 
         tiles
             .into_iter()
+            .filter(|pos| pos.x < BOARD_WIDTH as i32 && pos.y < BOARD_HEIGHT as i32)
             .filter(|pos| self.at_position(pos).is_none())
             .map(|pos| (na::Point2::new(pos.x, pos.y), 1))
             .collect()
@@ -189,7 +543,355 @@ impl Default for Board {
         Board {
             tiles: Vec::with_capacity(BOARD_SIZE),
             mobs: Vec::with_capacity(100),
+            dying: Vec::new(),
+            gold: 0,
+            lives: STARTING_LIVES,
+            pending_spawns: Vec::new(),
+            events: Vec::new(),
+            wave: None,
+            mob_grid: SpatialGrid::new(
+                BOARD_WIDTH as f32 * TILE_SIZE,
+                BOARD_HEIGHT as f32 * TILE_SIZE,
+                MOB_GRID_CELL_SIZE,
+            ),
+            next_mob_id: 0,
             waypoints,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::animation::{Animation, AnimationSet, AnimationState, LoopMode};
+    use crate::game::mob::MovementClass;
+    use crate::world::TICK_LENGTH;
+
+    #[test]
+    fn cannot_place_on_occupied_tile() {
+        let mut board = Board::default();
+
+        assert_eq!(Ok(()), board.place(make_unit(10, 10)));
+        assert_eq!(Err(PlacementError::Occupied), board.place(make_unit(10, 10)));
+    }
+
+    #[test]
+    fn cannot_block_waypoint() {
+        let mut board = Board::default();
+
+        assert_eq!(Err(PlacementError::BlocksPath), board.place(make_unit(33, 19)));
+        assert!(board.tiles.is_empty());
+    }
+
+    #[test]
+    fn preview_leaves_board_untouched() {
+        let mut board = Board::default();
+        let preview = board.preview_placement(&na::Point2::new(10, 19)).unwrap();
+
+        assert!(board.tiles.is_empty());
+        assert!(!preview.is_empty());
+        assert!(!preview.contains(&na::Point2::new(10, 19)));
+        assert_eq!(
+            Err(PlacementError::BlocksPath),
+            board.preview_placement(&na::Point2::new(33, 19))
+        );
+    }
+
+    #[test]
+    fn sell_refunds_unit() {
+        let mut board = Board::default();
+        board.place(make_unit(10, 10)).unwrap();
+
+        assert_eq!(Ok(7), board.sell(&na::Point2::new(10, 10)));
+        assert_eq!(7, board.gold);
+        assert!(board.tiles.is_empty());
+        assert_eq!(Err(PlacementError::Empty), board.sell(&na::Point2::new(10, 10)));
+    }
+
+    #[test]
+    fn relocate_swaps_units() {
+        let mut board = Board::default();
+        board.place(make_unit(10, 10)).unwrap();
+        board.place(make_unit(12, 12)).unwrap();
+        board.tiles[0].damage = 1;
+
+        assert_eq!(
+            Ok(()),
+            board.relocate(&na::Point2::new(10, 10), &na::Point2::new(12, 12))
+        );
+        assert_eq!(1, board.at_position(&na::Point2::new(12, 12)).unwrap().damage);
+    }
+
+    #[test]
+    fn relocate_rejects_blocking_move() {
+        let mut board = Board::default();
+        board.place(make_unit(10, 10)).unwrap();
+
+        assert_eq!(
+            Err(PlacementError::BlocksPath),
+            board.relocate(&na::Point2::new(10, 10), &na::Point2::new(19, 5))
+        );
+        assert!(board.at_position(&na::Point2::new(10, 10)).is_some());
+    }
+
+    #[test]
+    fn hits_are_credited_to_the_unit() {
+        let mut board = make_fight(|unit| unit.damage = 150, 1, |_, _| {});
+        board.update(TICK_LENGTH);
+
+        let unit = board.at_position(&na::Point2::new(10, 10)).unwrap();
+
+        assert!(board.mobs.is_empty());
+        assert_eq!(5, board.gold);
+        assert_eq!(1, unit.kills);
+        assert_eq!(100, unit.damage_dealt);
+        assert!(match board.events.as_slice() {
+            [CombatEvent::UnitAttacked { .. }, CombatEvent::MobDamaged {
+                damage: 100,
+                ..
+            }, CombatEvent::MobKilled { bounty: 5, .. }] => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn killed_mobs_play_their_death() {
+        let mut board = Board::default();
+        let mut definition = make_mob_definition();
+        definition.health = 0;
+        definition.animations.insert(AnimationState::Death, {
+            let mut death = Animation::from_sprites(&[1, 2], 0.5);
+            death.loop_mode = LoopMode::Once;
+            death
+        });
+        board
+            .mobs
+            .push(MobEntity::new(0, &definition, &na::Point2::new(12, 10)));
+
+        board.update(TICK_LENGTH);
+
+        assert!(board.mobs.is_empty());
+        assert_eq!(1, board.dying.len());
+        assert_eq!(1, board.dying[0].sprite_id());
+
+        board.update_animations(0.75);
+        assert_eq!(2, board.dying[0].sprite_id());
+
+        board.update_animations(0.25);
+        assert!(board.dying.is_empty());
+    }
+
+    #[test]
+    fn leaked_mobs_cost_lives() {
+        let mut board = Board::default();
+        let mut mob = MobEntity::new(0, &make_mob_definition(), &na::Point2::new(33, 33));
+        mob.status = MobEntityStatus::FinishedPath;
+        board.mobs.push(mob);
+
+        board.remove_leaked_mobs();
+
+        assert!(board.mobs.is_empty());
+        assert_eq!(STARTING_LIVES - 2, board.lives);
+        assert!(match board.events.as_slice() {
+            [CombatEvent::MobLeaked { life_cost: 2, .. }] => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn target_priority_picks_the_mob() {
+        let target_of = |priority: TargetPriority| {
+            let configure_unit = |unit: &mut Unit| {
+                unit.damage = 1;
+                unit.target_priority = priority;
+            };
+
+            // Later mobs are further along, so progress and spawn order disagree.
+            let mut board = make_fight(configure_unit, 4, |index, mob| {
+                mob.max_health = [100, 200, 30, 80][index];
+                mob.current_health = mob.max_health;
+                mob.path_index = index as u32;
+            });
+            board.update(TICK_LENGTH);
+
+            board
+                .mobs
+                .iter()
+                .position(|mob| mob.current_health < mob.max_health)
+        };
+
+        assert_eq!(Some(3), target_of(TargetPriority::First));
+        assert_eq!(Some(0), target_of(TargetPriority::Last));
+        assert_eq!(Some(1), target_of(TargetPriority::Strongest));
+        assert_eq!(Some(2), target_of(TargetPriority::Weakest));
+    }
+
+    #[test]
+    fn units_only_hit_the_mobs_they_target() {
+        let hits = |targets: Targets, movement: MovementClass| {
+            let mut board = make_fight(|unit| unit.targets = targets, 1, |_, mob| {
+                mob.movement = movement;
+            });
+            board.update(TICK_LENGTH);
+
+            board.at_position(&na::Point2::new(10, 10)).unwrap().damage_dealt > 0
+        };
+
+        assert!(!hits(Targets::Air, MovementClass::Ground));
+        assert!(hits(Targets::Air, MovementClass::Flying));
+        assert!(!hits(Targets::Ground, MovementClass::Flying));
+        assert!(hits(Targets::Ground, MovementClass::Ground));
+    }
+
+    #[test]
+    fn flying_mobs_fly_over_the_maze() {
+        let mut board = Board::default();
+        board.place(make_unit(20, 19)).unwrap();
+
+        let checkpoints = board.checkpoints();
+        let ground_path = board.calculate_lane_paths().unwrap().concat();
+
+        assert!(!ground_path.contains(&na::Point2::new(20, 19)));
+
+        let mut definition = make_mob_definition();
+        definition.movement = MovementClass::Flying;
+        definition.movement_speed = 100.0;
+        let mut mob = MobEntity::new(0, &definition, &board.spawn_point());
+        let mut destinations = vec![mob.destination];
+
+        for _ in 0..5000 {
+            mob.update(0.01);
+
+            if mob.status == MobEntityStatus::FinishedPath {
+                mob.follow_path(&checkpoints);
+
+                if destinations.last() != Some(&mob.destination) {
+                    destinations.push(mob.destination);
+                }
+            }
+        }
+
+        assert_eq!(checkpoints, destinations);
+    }
+
+    /// Cooldowns run on ticks, so twice the ticks gives twice the attacks whatever the frame rate.
+    #[test]
+    fn attacks_scale_with_ticks() {
+        let attacks_after = |ticks: u32| {
+            let configure_unit = |unit: &mut Unit| {
+                unit.damage = 1;
+                unit.attack_speed = 2.0;
+            };
+            let mut board = make_fight(configure_unit, 1, |_, mob| {
+                mob.max_health = 10_000;
+                mob.current_health = mob.max_health;
+            });
+
+            for _ in 0..ticks {
+                board.update(TICK_LENGTH);
+            }
+
+            board.at_position(&na::Point2::new(10, 10)).unwrap().damage_dealt
+        };
+
+        assert_eq!(2, attacks_after(60));
+        assert_eq!(4, attacks_after(120));
+    }
+
+    #[test]
+    fn disables_last_their_game_time() {
+        let mut unit = make_unit(10, 10);
+        unit.attacks = true;
+        unit.disable(0.5);
+
+        for _ in 0..29 {
+            unit.tick(TICK_LENGTH);
+        }
+        assert_eq!(None, unit.check_attack());
+
+        unit.tick(TICK_LENGTH);
+        assert_eq!(Some(unit.damage), unit.check_attack());
+    }
+
+    #[test]
+    fn waves_are_cleared_once_spawned_and_dealt_with() {
+        let mut board = Board::default();
+        let definition = make_mob_definition();
+        board.start_wave(3, 2);
+
+        board.spawn_wave(&[&definition]);
+        board.mobs.clear();
+        board.update(TICK_LENGTH);
+        assert!(board.events.is_empty());
+
+        board.spawn_wave(&[]);
+        board.update(TICK_LENGTH);
+        board.update(TICK_LENGTH);
+
+        assert!(!board.wave_in_progress());
+        assert_eq!(vec![CombatEvent::WaveCleared { round: 3 }], board.events);
+    }
+
+    #[test]
+    fn mobs_get_their_own_ids() {
+        let mut board = Board::default();
+        let definition = make_mob_definition();
+
+        board.spawn_wave(&[&definition, &definition]);
+        board.spawn_wave(&[&definition]);
+
+        let ids: Vec<u64> = board.mobs.iter().map(|mob| mob.id).collect();
+        assert_eq!(vec![0, 1, 2], ids);
+    }
+
+    /// A board with an attacking unit at (10, 10) that reaches the whole board, and
+    /// `mob_count` mobs next to it, each configured along with its index.
+    fn make_fight(
+        configure_unit: impl FnOnce(&mut Unit),
+        mob_count: usize,
+        configure_mob: impl Fn(usize, &mut MobEntity),
+    ) -> Board {
+        let mut board = Board::default();
+        let mut unit = make_unit(10, 10);
+        unit.attacks = true;
+        unit.range = 1000.0;
+        configure_unit(&mut unit);
+        board.place(unit).unwrap();
+
+        for index in 0..mob_count {
+            let mut mob =
+                MobEntity::new(index as u64, &make_mob_definition(), &na::Point2::new(12, 10));
+            configure_mob(index, &mut mob);
+            board.mobs.push(mob);
+        }
+
+        board
+    }
+
+    fn make_unit(x: i32, y: i32) -> Unit {
+        Unit {
+            tile_position: na::Point2::new(x, y),
+            ..Unit::default()
+        }
+    }
+
+    fn make_mob_definition() -> MobDefinition {
+        MobDefinition {
+            health: 100,
+            physical_defense: 0,
+            magical_defense: 0,
+            invisible: false,
+            spritesheet_id: 0,
+            movement_speed: 0.0,
+            bounty: 5,
+            life_cost: 2,
+            size: 16.0,
+            sprite_frames: vec![],
+            animations: AnimationSet::new(),
+            movement: MovementClass::Ground,
+            name: String::new(),
+            boss: false,
+            abilities: vec![],
+        }
+    }
+}
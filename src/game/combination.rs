@@ -1,6 +1,14 @@
-use crate::game::unit::{Unit, UnitType};
+use ggez::nalgebra as na;
 
-static COMBINATIONS: &'static [(UnitType, [(UnitType, u16); 3])] = &[
+use crate::game::unit::{Rank, Unit, UnitType};
+
+/// Number of identical units (same type and rank) consumed by a rank-up merge.
+pub const MERGE_COUNT: usize = 3;
+
+/// A unit that can be made, along with the type and rank of each unit it takes.
+pub type Recipe = (UnitType, [(UnitType, Rank); 3]);
+
+static COMBINATIONS: &'static [Recipe] = &[
     (
         UnitType::Knight,
         [
@@ -19,8 +27,31 @@ static COMBINATIONS: &'static [(UnitType, [(UnitType, u16); 3])] = &[
     ),
 ];
 
+#[derive(Debug, PartialEq)]
+pub enum MergeError {
+    NoUnit,
+    InsufficientUnits,
+    MaxRank,
+}
+
+pub fn recipes() -> &'static [Recipe] {
+    COMBINATIONS
+}
+
 pub trait Combination {
     fn combinations(&self) -> Vec<UnitType>;
+
+    /// The available combinations that would use up `unit`.
+    fn combinations_with(&self, unit: &Unit) -> Vec<UnitType>;
+
+    /// Positions of every unit that could go into making `result`.
+    fn ingredients(&self, result: UnitType) -> Vec<na::Point2<i32>>;
+}
+
+pub trait Merge {
+    /// Merges the unit at `position` with other units of the same type and rank,
+    /// removing them and promoting the unit at `position` to the next rank.
+    fn merge(&mut self, position: &na::Point2<i32>) -> Result<Rank, MergeError>;
 }
 
 impl Combination for Vec<Unit> {
@@ -43,11 +74,92 @@ impl Combination for Vec<Unit> {
             })
             .collect()
     }
+
+    fn combinations_with(&self, unit: &Unit) -> Vec<UnitType> {
+        let available = self.combinations();
+
+        COMBINATIONS
+            .iter()
+            .filter(|(unit_type, requirements)| {
+                available.contains(unit_type)
+                    && requirements
+                        .iter()
+                        .any(|(required_unit_type, required_rank)| {
+                            unit.unit_type == *required_unit_type && unit.rank == *required_rank
+                        })
+            })
+            .map(|(unit_type, _)| *unit_type)
+            .collect()
+    }
+
+    fn ingredients(&self, result: UnitType) -> Vec<na::Point2<i32>> {
+        let requirements = match COMBINATIONS
+            .iter()
+            .find(|(unit_type, _)| *unit_type == result)
+        {
+            Some((_, requirements)) => requirements,
+            None => return Vec::new(),
+        };
+
+        self.iter()
+            .filter(|unit| {
+                requirements
+                    .iter()
+                    .any(|(required_unit_type, required_rank)| {
+                        unit.unit_type == *required_unit_type && unit.rank == *required_rank
+                    })
+            })
+            .map(|unit| unit.tile_position)
+            .collect()
+    }
+}
+
+impl Merge for Vec<Unit> {
+    fn merge(&mut self, position: &na::Point2<i32>) -> Result<Rank, MergeError> {
+        let mut target = self
+            .iter()
+            .position(|unit| unit.tile_position == *position)
+            .ok_or(MergeError::NoUnit)?;
+
+        let unit_type = self[target].unit_type;
+        let rank = self[target].rank;
+
+        if rank >= self[target].max_rank() {
+            return Err(MergeError::MaxRank);
+        }
+
+        let consumed: Vec<usize> = self
+            .iter()
+            .enumerate()
+            .filter(|(index, unit)| {
+                *index != target && unit.unit_type == unit_type && unit.rank == rank
+            })
+            .map(|(index, _)| index)
+            .take(MERGE_COUNT - 1)
+            .collect();
+
+        if consumed.len() < MERGE_COUNT - 1 {
+            return Err(MergeError::InsufficientUnits);
+        }
+
+        for index in consumed.into_iter().rev() {
+            self.remove(index);
+
+            if index < target {
+                target -= 1;
+            }
+        }
+
+        self[target].set_rank(rank + 1);
+
+        Ok(rank + 1)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::unit::RankStats;
 
     #[test]
     fn empty_combinations() {
@@ -71,6 +183,125 @@ mod tests {
         assert_eq!(vec![UnitType::Knight], combinations);
     }
 
+    #[test]
+    fn combinations_with_unit() {
+        let units = vec![
+            make_unit(UnitType::Warrior),
+            make_unit(UnitType::Mage),
+            make_unit(UnitType::Ranger),
+            make_unit(UnitType::Cleric),
+        ];
+
+        assert_eq!(vec![UnitType::Knight], units.combinations_with(&units[1]));
+        assert!(units.combinations_with(&units[3]).is_empty());
+    }
+
+    #[test]
+    fn ingredients_on_board() {
+        let units = vec![
+            make_ranked_unit(UnitType::Warrior, 0),
+            make_ranked_unit(UnitType::Cleric, 1),
+            make_ranked_unit(UnitType::Ranger, 2),
+        ];
+
+        assert_eq!(
+            vec![na::Point2::new(0, 0), na::Point2::new(2, 0)],
+            units.ingredients(UnitType::Knight)
+        );
+        assert!(units.ingredients(UnitType::Sage).is_empty());
+    }
+
+    #[test]
+    fn merge_promotes_unit() {
+        let mut units = vec![
+            make_ranked_unit(UnitType::Warrior, 0),
+            make_ranked_unit(UnitType::Mage, 1),
+            make_ranked_unit(UnitType::Warrior, 2),
+            make_ranked_unit(UnitType::Warrior, 3),
+        ];
+
+        assert_eq!(Ok(2), units.merge(&na::Point2::new(2, 0)));
+        assert_eq!(2, units.len());
+
+        let merged = units
+            .iter()
+            .find(|unit| unit.tile_position == na::Point2::new(2, 0))
+            .unwrap();
+
+        assert_eq!(2, merged.rank);
+        assert_eq!(20, merged.damage);
+        assert_eq!(UnitType::Mage, units[0].unit_type);
+    }
+
+    #[test]
+    fn merge_requires_enough_units() {
+        let mut units = vec![
+            make_ranked_unit(UnitType::Warrior, 0),
+            make_ranked_unit(UnitType::Warrior, 1),
+            make_ranked_unit(UnitType::Mage, 2),
+        ];
+
+        assert_eq!(
+            Err(MergeError::InsufficientUnits),
+            units.merge(&na::Point2::new(0, 0))
+        );
+        assert_eq!(3, units.len());
+    }
+
+    #[test]
+    fn merge_ignores_other_ranks() {
+        let mut units = vec![
+            make_ranked_unit(UnitType::Warrior, 0),
+            make_ranked_unit(UnitType::Warrior, 1),
+            make_ranked_unit(UnitType::Warrior, 2),
+        ];
+        units[2].set_rank(2);
+
+        assert_eq!(
+            Err(MergeError::InsufficientUnits),
+            units.merge(&na::Point2::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn merge_stops_at_max_rank() {
+        let mut units: Vec<Unit> = (0..3)
+            .map(|x| {
+                let mut unit = make_ranked_unit(UnitType::Warrior, x);
+                unit.set_rank(3);
+                unit
+            })
+            .collect();
+
+        assert_eq!(Err(MergeError::MaxRank), units.merge(&na::Point2::new(0, 0)));
+        assert_eq!(3, units.len());
+    }
+
+    #[test]
+    fn merge_without_unit() {
+        let mut units = vec![make_ranked_unit(UnitType::Warrior, 0)];
+
+        assert_eq!(Err(MergeError::NoUnit), units.merge(&na::Point2::new(5, 5)));
+    }
+
+    fn make_ranked_unit(unit_type: UnitType, x: i32) -> Unit {
+        let ranks = (1..=3)
+            .map(|rank| RankStats {
+                range: rank as f32,
+                damage: rank * 10,
+                attack_speed: 1.0,
+            })
+            .collect();
+
+        Unit {
+            unit_type,
+            ranks,
+            damage: 10,
+            tile_position: na::Point2::new(x, 0),
+            ..Unit::default()
+        }
+    }
+
     fn make_unit<'a>(unit_type: UnitType) -> Unit {
         Unit {
             unit_type: unit_type,
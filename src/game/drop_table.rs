@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::game::unit::{Rank, UnitType};
+
+/// Drop weights used from `from_round` onwards, until a later bracket takes over.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DropTableBracket {
+    pub from_round: u32,
+    pub unit_types: HashMap<UnitType, f64>,
+    pub ranks: HashMap<Rank, f64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DropTables {
+    pub brackets: Vec<DropTableBracket>,
+}
+
+impl DropTables {
+    pub fn bracket(&self, round: u32) -> Option<&DropTableBracket> {
+        self.brackets
+            .iter()
+            .filter(|bracket| bracket.from_round <= round)
+            .max_by_key(|bracket| bracket.from_round)
+    }
+}
+
+impl DropTableBracket {
+    pub fn roll_unit_type<R: Rng>(&self, rng: &mut R) -> Option<UnitType> {
+        roll(&self.unit_types, rng)
+    }
+
+    pub fn roll_rank<R: Rng>(&self, rng: &mut R) -> Option<Rank> {
+        roll(&self.ranks, rng)
+    }
+
+    pub fn unit_type_odds(&self) -> Vec<(UnitType, f64)> {
+        odds(&self.unit_types)
+    }
+
+    pub fn rank_odds(&self) -> Vec<(Rank, f64)> {
+        odds(&self.ranks)
+    }
+}
+
+/// The units rolled during one round. Its rng only depends on the seed and the round,
+/// so a save can replay the round by rolling the same number of times.
+#[derive(Debug, Clone)]
+pub struct RoundRolls {
+    rng: StdRng,
+    count: u32,
+}
+
+impl RoundRolls {
+    pub fn new(seed: u64, round: u32) -> Self {
+        RoundRolls {
+            rng: round_rng(seed, round),
+            count: 0,
+        }
+    }
+
+    /// Units rolled so far.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// The unit `roll` would give next, without rolling it.
+    pub fn peek(&self, bracket: &DropTableBracket) -> Option<(UnitType, Rank)> {
+        self.clone().roll(bracket)
+    }
+
+    pub fn roll(&mut self, bracket: &DropTableBracket) -> Option<(UnitType, Rank)> {
+        self.count += 1;
+
+        Some((
+            bracket.roll_unit_type(&mut self.rng)?,
+            bracket.roll_rank(&mut self.rng)?,
+        ))
+    }
+}
+
+/// Every round gets its own rng, so a round's rolls only depend on the seed and the round.
+fn round_rng(seed: u64, round: u32) -> StdRng {
+    StdRng::seed_from_u64(seed ^ u64::from(round).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// Picks a key by weight. Keys are sorted first, since `HashMap` iteration order
+/// differs between runs and would otherwise make seeded games unrepeatable.
+fn roll<T: Copy + Ord, R: Rng>(weights: &HashMap<T, f64>, rng: &mut R) -> Option<T> {
+    let mut entries: Vec<(T, f64)> = weights
+        .iter()
+        .map(|(key, weight)| (*key, *weight))
+        .collect();
+    entries.sort_by_key(|(key, _)| *key);
+
+    let index = WeightedIndex::new(entries.iter().map(|(_, weight)| *weight)).ok()?;
+
+    Some(entries[index.sample(rng)].0)
+}
+
+/// Normalizes weights into probabilities, most likely first.
+fn odds<T: Copy + Ord + Hash>(weights: &HashMap<T, f64>) -> Vec<(T, f64)> {
+    let total: f64 = weights.values().sum();
+
+    let mut odds: Vec<(T, f64)> = weights
+        .iter()
+        .map(|(key, weight)| (*key, if total > 0.0 { weight / total } else { 0.0 }))
+        .collect();
+
+    odds.sort_by(|(key_a, a), (key_b, b)| b.partial_cmp(a).unwrap().then(key_a.cmp(key_b)));
+    odds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_latest_bracket() {
+        let tables = DropTables {
+            brackets: vec![make_bracket(1), make_bracket(10), make_bracket(5)],
+        };
+
+        assert_eq!(1, tables.bracket(4).unwrap().from_round);
+        assert_eq!(5, tables.bracket(5).unwrap().from_round);
+        assert_eq!(10, tables.bracket(30).unwrap().from_round);
+        assert!(tables.bracket(0).is_none());
+    }
+
+    #[test]
+    fn normalizes_odds() {
+        let mut bracket = make_bracket(1);
+        bracket.ranks.insert(1, 3.0);
+        bracket.ranks.insert(2, 1.0);
+
+        assert_eq!(vec![(1, 0.75), (2, 0.25)], bracket.rank_odds());
+    }
+
+    #[test]
+    fn seeded_rolls_repeat() {
+        let mut bracket = make_bracket(1);
+
+        for (rank, weight) in &[(1, 5.0), (2, 3.0), (3, 1.0), (4, 1.0)] {
+            bracket.ranks.insert(*rank, *weight);
+        }
+
+        let rolls = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..20)
+                .map(|_| bracket.roll_rank(&mut rng).unwrap())
+                .collect::<Vec<Rank>>()
+        };
+
+        assert_eq!(rolls(42), rolls(42));
+    }
+
+    #[test]
+    fn empty_bracket_rolls_nothing() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert_eq!(None, make_bracket(1).roll_unit_type(&mut rng));
+    }
+
+    fn make_bracket(from_round: u32) -> DropTableBracket {
+        DropTableBracket {
+            from_round,
+            unit_types: HashMap::new(),
+            ranks: HashMap::new(),
+        }
+    }
+}
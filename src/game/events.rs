@@ -0,0 +1,32 @@
+use ggez::nalgebra as na;
+
+use crate::game::unit::DamageType;
+
+/// Something that happened during the simulation, for the scene to react to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CombatEvent {
+    UnitAttacked {
+        unit: na::Point2<i32>,
+        mob: u64,
+    },
+    MobDamaged {
+        mob: u64,
+        position: na::Point2<f32>,
+        damage: u32,
+        damage_type: DamageType,
+    },
+    MobKilled {
+        mob: u64,
+        position: na::Point2<f32>,
+        bounty: u32,
+    },
+    MobLeaked {
+        mob: u64,
+        position: na::Point2<f32>,
+        life_cost: u32,
+    },
+    /// Every mob of the round has been spawned and dealt with.
+    WaveCleared {
+        round: u32,
+    },
+}
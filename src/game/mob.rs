@@ -1,16 +1,100 @@
+use std::cmp::Ordering;
 use std::time::Instant;
 
 use ggez::nalgebra as na;
 use serde::Deserialize;
 
-// @TODO(vy): remove copy
-#[derive(Debug, Deserialize, Clone, Copy)]
+use crate::game::animation::{
+    Animation, AnimationSet, AnimationState, Animator, Direction, DEFAULT_FRAME_DURATION,
+};
+use crate::game::navigation::FlowField;
+use crate::screen::Transform;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum MovementClass {
+    /// Walks the maze, following the path around units.
+    Ground,
+    /// Flies straight between waypoints, ignoring units.
+    Flying,
+}
+
+impl Default for MovementClass {
+    fn default() -> Self {
+        MovementClass::Ground
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum AbilityTrigger {
+    /// Fires every given number of seconds.
+    Timer(f32),
+    /// Fires once, when health drops below the given fraction of max health.
+    HealthBelow(f32),
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub enum AbilityEffect {
+    SpawnMinions { mob_id: String, count: u32 },
+    HealNearby { radius: f32, amount: i32 },
+    Invulnerable { duration: f32 },
+    SpeedBurst { multiplier: f32, duration: f32 },
+    DisableUnits { radius: f32, duration: f32 },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Ability {
+    pub trigger: AbilityTrigger,
+    pub effect: AbilityEffect,
+}
+
+#[derive(Debug, Clone)]
+struct AbilityState {
+    ability: Ability,
+    elapsed: f32,
+    fired: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
 pub struct MobDefinition {
     pub health: i32,
     pub physical_defense: i32,
     pub magical_defense: i32,
     pub invisible: bool,
+    /// The sheet the mob's sprites are on, as an index into the level's sprite layer.
     pub spritesheet_id: u32,
+    pub movement_speed: f32,
+    /// Gold awarded for killing the mob.
+    pub bounty: u32,
+    /// Lives lost when the mob reaches the end of the path.
+    pub life_cost: u32,
+    /// Diameter of the mob in pixels, used for hit detection and health bars.
+    pub size: f32,
+    pub sprite_frames: Vec<i32>,
+    #[serde(default)]
+    pub movement: MovementClass,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub boss: bool,
+    #[serde(default)]
+    pub abilities: Vec<Ability>,
+    #[serde(default)]
+    pub animations: AnimationSet,
+}
+
+impl MobDefinition {
+    /// The mob's animations, with `sprite_frames` looping while idle if none is given for it.
+    fn animation_set(&self) -> AnimationSet {
+        let mut animations = self.animations.clone();
+
+        if !self.sprite_frames.is_empty() {
+            animations.entry(AnimationState::Idle).or_insert_with(|| {
+                Animation::from_sprites(&self.sprite_frames, DEFAULT_FRAME_DURATION)
+            });
+        }
+
+        animations
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -21,45 +105,207 @@ pub enum MobEntityStatus {
 
 #[derive(Debug, Clone)]
 pub struct MobEntity {
+    /// Tells mobs apart in combat events, since their indices change as mobs die.
+    pub id: u64,
     pub position: na::Point2<f32>,
     pub last_position: na::Point2<f32>,
-    pub destination: na::Point2<f32>,
+    /// The tile the mob is currently walking towards.
+    pub destination: na::Point2<i32>,
     pub path_index: u32,
+    /// Index of the waypoint being walked towards, when following flow fields.
+    pub lane: usize,
     pub movement_speed: f32,
     pub max_health: i32,
     pub current_health: i32,
     pub physical_defense: i32,
     pub magical_defense: i32,
     pub invisible: bool,
+    pub bounty: u32,
+    pub life_cost: u32,
+    pub size: f32,
+    pub spritesheet_id: u32,
+    pub sprite_frames: Vec<i32>,
+    pub animations: AnimationSet,
+    pub animator: Animator,
+    pub movement: MovementClass,
+    pub name: String,
+    pub boss: bool,
     pub status: MobEntityStatus,
     pub last_damaged_at: Instant,
+    /// Effects that need the rest of the board to resolve, drained by `Board::update`.
+    pub triggered_effects: Vec<AbilityEffect>,
+    abilities: Vec<AbilityState>,
+    invulnerable_for: f32,
+    speed_multiplier: f32,
+    speed_burst_for: f32,
 }
 
 impl MobEntity {
+    /// Creates a mob standing on the given tile, walking towards the start of the path.
+    /// The board hands out the `id`.
+    pub fn new(id: u64, definition: &MobDefinition, spawn: &na::Point2<i32>) -> Self {
+        let spawn_position = Transform::tile_center(spawn);
+
+        MobEntity {
+            id,
+            position: spawn_position,
+            last_position: spawn_position,
+            destination: *spawn,
+            path_index: 0,
+            lane: 0,
+            status: MobEntityStatus::Walking,
+            max_health: definition.health,
+            current_health: definition.health,
+            physical_defense: definition.physical_defense,
+            magical_defense: definition.magical_defense,
+            invisible: definition.invisible,
+            movement_speed: definition.movement_speed,
+            bounty: definition.bounty,
+            life_cost: definition.life_cost,
+            size: definition.size,
+            spritesheet_id: definition.spritesheet_id,
+            sprite_frames: definition.sprite_frames.clone(),
+            animations: definition.animation_set(),
+            animator: Animator::default(),
+            movement: definition.movement,
+            name: definition.name.clone(),
+            boss: definition.boss,
+            last_damaged_at: Instant::now(),
+            triggered_effects: Vec::new(),
+            abilities: definition
+                .abilities
+                .iter()
+                .map(|ability| AbilityState {
+                    ability: ability.clone(),
+                    elapsed: 0.0,
+                    fired: false,
+                })
+                .collect(),
+            invulnerable_for: 0.0,
+            speed_multiplier: 1.0,
+            speed_burst_for: 0.0,
+        }
+    }
+
     pub fn update(&mut self, dt: f32) {
+        self.update_abilities(dt);
+        self.animator.update(dt);
+
         if self.status != MobEntityStatus::FinishedPath {
-            // @TODO(vy): This detects whether the mob is at the designated tile. These magic
-            // numbers are hardcoded, which represents that a tile is 16px wide & high.
-            let world_destination =
-                na::Point2::<f32>::new(self.destination.x * 16.0, self.destination.y * 16.0);
+            let world_destination = Transform::tile_center(&self.destination);
             let distance = na::distance(&self.position, &world_destination);
             if distance < 1.0 {
                 self.status = MobEntityStatus::FinishedPath;
             } else {
                 let diff: na::Vector2<f32> = world_destination - self.position;
-                let new_position = self.position + diff.normalize() * self.movement_speed * dt;
+                let new_position = self.position + diff.normalize() * self.current_speed() * dt;
 
                 self.last_position = self.position;
                 self.position = new_position;
+
+                self.animator.set_direction(Direction::from_vector(&diff));
+                self.animator.set_state(AnimationState::Walk);
+            }
+        }
+    }
+
+    /// Heads for the next point of `path`, once the current destination is reached.
+    pub fn follow_path(&mut self, path: &[na::Point2<i32>]) {
+        self.path_index += 1;
+
+        if let Some(point) = path.get(self.path_index as usize) {
+            self.destination = *point;
+            self.status = MobEntityStatus::Walking;
+        }
+    }
+
+    /// Heads for the next tile given by the flow field of the current lane, moving
+    /// on to the next lane once its goal is reached. A mob on a tile the field can't
+    /// route from holds its position rather than walking through units.
+    pub fn follow_flow_fields(&mut self, fields: &[FlowField]) {
+        let tile = self.destination;
+
+        while let Some(field) = fields.get(self.lane) {
+            if tile == field.goal() {
+                self.lane += 1;
+                continue;
             }
+
+            // Walking back to its own tile keeps the mob from counting as leaked.
+            self.destination = field.next_step(&tile).unwrap_or(tile);
+            self.status = MobEntityStatus::Walking;
+            return;
+        }
+    }
+
+    fn update_abilities(&mut self, dt: f32) {
+        self.invulnerable_for = (self.invulnerable_for - dt).max(0.0);
+        self.speed_burst_for = (self.speed_burst_for - dt).max(0.0);
+
+        let health_fraction = self.current_health as f32 / self.max_health as f32;
+        let mut triggered = Vec::new();
+
+        for state in self.abilities.iter_mut() {
+            match state.ability.trigger {
+                AbilityTrigger::Timer(period) => {
+                    state.elapsed += dt;
+
+                    if state.elapsed >= period {
+                        state.elapsed -= period;
+                        triggered.push(state.ability.effect.clone());
+                    }
+                }
+                AbilityTrigger::HealthBelow(threshold) => {
+                    if !state.fired && health_fraction < threshold {
+                        state.fired = true;
+                        triggered.push(state.ability.effect.clone());
+                    }
+                }
+            }
+        }
+
+        for effect in triggered {
+            match effect {
+                AbilityEffect::Invulnerable { duration } => {
+                    self.invulnerable_for = self.invulnerable_for.max(duration);
+                }
+                AbilityEffect::SpeedBurst {
+                    multiplier,
+                    duration,
+                } => {
+                    self.speed_multiplier = multiplier;
+                    self.speed_burst_for = duration;
+                }
+                effect => self.triggered_effects.push(effect),
+            }
+        }
+    }
+
+    pub fn current_speed(&self) -> f32 {
+        if self.speed_burst_for > 0.0 {
+            self.movement_speed * self.speed_multiplier
+        } else {
+            self.movement_speed
         }
     }
 
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_for > 0.0
+    }
+
     pub fn damage(&mut self, damage: u32) {
+        if self.is_invulnerable() {
+            return;
+        }
+
         self.current_health -= damage as i32;
         self.last_damaged_at = Instant::now();
     }
 
+    pub fn heal(&mut self, amount: i32) {
+        self.current_health = (self.current_health + amount).min(self.max_health);
+    }
+
     pub fn show_health_bar(&self) -> bool {
         self.last_damaged_at.elapsed().as_secs() < 5
     }
@@ -67,23 +313,188 @@ impl MobEntity {
     pub fn is_alive(&self) -> bool {
         self.current_health > 0
     }
+
+    /// Orders mobs by how far along their route they are: by lane and step along the
+    /// path first, then by how close they are to the tile they are heading for.
+    pub fn compare_progress(&self, other: &MobEntity) -> Ordering {
+        let remaining = |mob: &MobEntity| {
+            na::distance(&mob.position, &Transform::tile_center(&mob.destination))
+        };
+
+        (self.lane, self.path_index)
+            .cmp(&(other.lane, other.path_index))
+            .then_with(|| {
+                remaining(other)
+                    .partial_cmp(&remaining(self))
+                    .unwrap_or(Ordering::Equal)
+            })
+    }
+
+    /// Starts the death animation. The mob can be removed once `death_finished`.
+    pub fn die(&mut self) {
+        self.animator.play(AnimationState::Death);
+    }
+
+    pub fn death_finished(&self) -> bool {
+        self.animator.state() == AnimationState::Death
+            && self.animator.is_finished(&self.animations)
+    }
+
+    pub fn sprite_id(&self) -> i32 {
+        self.animator
+            .sprite_id(&self.animations)
+            .or_else(|| self.sprite_frames.first().copied())
+            .unwrap_or(0)
+    }
 }
 
-impl From<&MobDefinition> for MobEntity {
-    fn from(definition: &MobDefinition) -> Self {
-        MobEntity {
-            position: na::Point2::new(80.0, 80.0),
-            last_position: na::Point2::new(5.0, 19.0),
-            destination: na::Point2::new(5.0, 19.0),
-            path_index: 0,
-            status: MobEntityStatus::Walking,
-            max_health: definition.health,
-            current_health: definition.health,
-            physical_defense: definition.physical_defense,
-            magical_defense: definition.magical_defense,
-            invisible: definition.invisible,
-            movement_speed: 50.0,
-            last_damaged_at: Instant::now(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_threshold_fires_once() {
+        let mut mob = make_mob(vec![Ability {
+            trigger: AbilityTrigger::HealthBelow(0.5),
+            effect: AbilityEffect::Invulnerable { duration: 1.0 },
+        }]);
+
+        mob.update(0.1);
+        assert!(!mob.is_invulnerable());
+
+        mob.damage(60);
+        mob.update(0.1);
+        assert!(mob.is_invulnerable());
+
+        mob.damage(10);
+        assert_eq!(40, mob.current_health);
+
+        mob.update(1.0);
+        mob.update(0.1);
+        assert!(!mob.is_invulnerable());
+    }
+
+    #[test]
+    fn timer_queues_board_effects() {
+        let effect = AbilityEffect::HealNearby {
+            radius: 32.0,
+            amount: 5,
+        };
+        let mut mob = make_mob(vec![Ability {
+            trigger: AbilityTrigger::Timer(1.0),
+            effect: effect.clone(),
+        }]);
+
+        mob.update(0.5);
+        assert!(mob.triggered_effects.is_empty());
+
+        mob.update(0.5);
+        assert_eq!(vec![effect], mob.triggered_effects);
+    }
+
+    #[test]
+    fn speed_burst_expires() {
+        let mut mob = make_mob(vec![Ability {
+            trigger: AbilityTrigger::Timer(1.0),
+            effect: AbilityEffect::SpeedBurst {
+                multiplier: 2.0,
+                duration: 0.5,
+            },
+        }]);
+
+        mob.update(1.0);
+        assert_eq!(100.0, mob.current_speed());
+
+        mob.update(0.5);
+        assert_eq!(50.0, mob.current_speed());
+    }
+
+    #[test]
+    fn progress_follows_the_route() {
+        let mut behind = make_mob(vec![]);
+        behind.path_index = 3;
+        behind.destination = na::Point2::new(5, 0);
+
+        let mut ahead = behind.clone();
+        ahead.position = Transform::tile_center(&na::Point2::new(4, 0));
+
+        assert_eq!(Ordering::Less, behind.compare_progress(&ahead));
+
+        behind.path_index = 4;
+        assert_eq!(Ordering::Greater, behind.compare_progress(&ahead));
+
+        ahead.lane = 1;
+        assert_eq!(Ordering::Less, behind.compare_progress(&ahead));
+    }
+
+    #[test]
+    fn flow_fields_never_lead_into_walls() {
+        let walls = vec![
+            na::Point2::new(2, 0),
+            na::Point2::new(2, 1),
+            na::Point2::new(0, 3),
+            na::Point2::new(1, 4),
+        ];
+        let neighbours = |point: &na::Point2<i32>| {
+            [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .map(|(dx, dy)| na::Point2::new(point.x + dx, point.y + dy))
+                .filter(|neighbour| !walls.contains(neighbour))
+                .collect::<Vec<_>>()
+        };
+        let goal = na::Point2::new(4, 0);
+        let fields = vec![FlowField::new(5, 5, goal, neighbours)];
+
+        let mut mob = make_mob(vec![]);
+
+        for _ in 0..2000 {
+            mob.update(0.01);
+
+            if mob.status == MobEntityStatus::FinishedPath {
+                mob.follow_flow_fields(&fields);
+            }
+
+            assert!(!walls.contains(&mob.destination));
         }
+
+        assert_eq!(goal, mob.destination);
+
+        let mut stranded = make_mob(vec![]);
+        stranded.destination = na::Point2::new(0, 4);
+        stranded.position = Transform::tile_center(&stranded.destination);
+
+        for _ in 0..10 {
+            stranded.update(0.01);
+            stranded.follow_flow_fields(&fields);
+        }
+
+        assert_eq!(na::Point2::new(0, 4), stranded.destination);
+        assert_eq!(MobEntityStatus::Walking, stranded.status);
+        assert_eq!(
+            Transform::tile_center(&na::Point2::new(0, 4)),
+            stranded.position
+        );
+    }
+
+    fn make_mob(abilities: Vec<Ability>) -> MobEntity {
+        let definition = MobDefinition {
+            health: 100,
+            physical_defense: 0,
+            magical_defense: 0,
+            invisible: false,
+            spritesheet_id: 0,
+            movement_speed: 50.0,
+            bounty: 0,
+            life_cost: 1,
+            size: 16.0,
+            sprite_frames: vec![],
+            animations: AnimationSet::new(),
+            movement: MovementClass::Ground,
+            name: String::new(),
+            boss: true,
+            abilities,
+        };
+
+        MobEntity::new(0, &definition, &na::Point2::new(0, 0))
     }
 }
@@ -1,5 +1,12 @@
+pub mod animation;
 pub mod board;
 pub mod combination;
+pub mod drop_table;
+pub mod events;
 pub mod mob;
+pub mod navigation;
 pub mod player;
+pub mod registry;
+pub mod save;
+pub mod spatial;
 pub mod unit;
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+
+use ggez::nalgebra as na;
+
+/// How ground mobs find their way through the maze.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NavigationMode {
+    /// Mobs walk a single precomputed A* path, point by point.
+    AStar,
+    /// Mobs look up their next tile in a flow field, from wherever they are.
+    FlowField,
+}
+
+impl Default for NavigationMode {
+    fn default() -> Self {
+        NavigationMode::AStar
+    }
+}
+
+/// Distances to a goal tile for every reachable tile on the board, along with
+/// the next tile to step to, so any number of mobs can be routed in O(1).
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    width: usize,
+    height: usize,
+    goal: na::Point2<i32>,
+    distances: Vec<Option<u32>>,
+    next_steps: Vec<Option<na::Point2<i32>>>,
+}
+
+impl FlowField {
+    /// Builds the field with a breadth-first search outwards from `goal`. Moves are
+    /// symmetric, so the neighbours of a tile are also the tiles that can step onto it.
+    pub fn new<F>(width: usize, height: usize, goal: na::Point2<i32>, neighbours: F) -> Self
+    where
+        F: Fn(&na::Point2<i32>) -> Vec<na::Point2<i32>>,
+    {
+        let mut field = FlowField {
+            width,
+            height,
+            goal,
+            distances: vec![None; width * height],
+            next_steps: vec![None; width * height],
+        };
+
+        let goal_index = match field.index(&goal) {
+            Some(index) => index,
+            None => return field,
+        };
+
+        let mut queue = VecDeque::new();
+        field.distances[goal_index] = Some(0);
+        queue.push_back((goal, 0));
+
+        while let Some((current, distance)) = queue.pop_front() {
+            for neighbour in neighbours(&current) {
+                if let Some(index) = field.index(&neighbour) {
+                    if field.distances[index].is_none() {
+                        field.distances[index] = Some(distance + 1);
+                        field.next_steps[index] = Some(current);
+                        queue.push_back((neighbour, distance + 1));
+                    }
+                }
+            }
+        }
+
+        field
+    }
+
+    fn index(&self, point: &na::Point2<i32>) -> Option<usize> {
+        if point.x < 0
+            || point.y < 0
+            || point.x as usize >= self.width
+            || point.y as usize >= self.height
+        {
+            None
+        } else {
+            Some(point.y as usize * self.width + point.x as usize)
+        }
+    }
+
+    pub fn goal(&self) -> na::Point2<i32> {
+        self.goal
+    }
+
+    /// Number of steps from `point` to the goal, or `None` if it is unreachable.
+    pub fn distance(&self, point: &na::Point2<i32>) -> Option<u32> {
+        self.index(point).and_then(|index| self.distances[index])
+    }
+
+    /// The tile to walk to next from `point`, or `None` at the goal or when unreachable.
+    pub fn next_step(&self, point: &na::Point2<i32>) -> Option<na::Point2<i32>> {
+        self.index(point).and_then(|index| self.next_steps[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_neighbours(
+        walls: Vec<na::Point2<i32>>,
+    ) -> impl Fn(&na::Point2<i32>) -> Vec<na::Point2<i32>> {
+        move |point| {
+            let mut neighbours = Vec::with_capacity(4);
+
+            for (dx, dy) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let neighbour = na::Point2::new(point.x + dx, point.y + dy);
+
+                if !walls.contains(&neighbour) {
+                    neighbours.push(neighbour);
+                }
+            }
+
+            neighbours
+        }
+    }
+
+    #[test]
+    fn flows_towards_goal() {
+        let field = FlowField::new(5, 1, na::Point2::new(4, 0), open_neighbours(vec![]));
+
+        assert_eq!(Some(4), field.distance(&na::Point2::new(0, 0)));
+        assert_eq!(
+            Some(na::Point2::new(1, 0)),
+            field.next_step(&na::Point2::new(0, 0))
+        );
+        assert_eq!(None, field.next_step(&na::Point2::new(4, 0)));
+    }
+
+    #[test]
+    fn routes_around_walls() {
+        let walls = vec![na::Point2::new(1, 0), na::Point2::new(1, 1)];
+        let field = FlowField::new(3, 3, na::Point2::new(2, 0), open_neighbours(walls));
+
+        assert_eq!(Some(6), field.distance(&na::Point2::new(0, 0)));
+        assert_eq!(None, field.distance(&na::Point2::new(1, 0)));
+    }
+
+    #[test]
+    fn unreachable_tiles_have_no_step() {
+        let walls = vec![na::Point2::new(1, 0)];
+        let field = FlowField::new(3, 1, na::Point2::new(2, 0), open_neighbours(walls));
+
+        assert_eq!(None, field.distance(&na::Point2::new(0, 0)));
+        assert_eq!(None, field.next_step(&na::Point2::new(0, 0)));
+    }
+}
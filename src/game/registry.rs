@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use ggez::nalgebra as na;
+
+use crate::game::mob::MobDefinition;
+use crate::game::unit::{Rank, Unit, UnitType};
+use crate::resources;
+use crate::types::Error;
+
+/// Every unit definition found under `/units`, keyed by its `UnitType`.
+pub struct UnitRegistry {
+    units: HashMap<UnitType, Unit>,
+}
+
+impl UnitRegistry {
+    pub fn load(ctx: &mut ggez::Context, store: &mut resources::Store) -> Result<Self, Error> {
+        let paths = resources::find_ron_files(ctx, "/units").map_err(Error::GgezError)?;
+        let mut units = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let unit = store
+                .get::<resources::Unit>(&resources::Key::from_path(&path), ctx)
+                .map_err(|e| Error::ResourceError(format!("{:?}", e)))?;
+
+            units.push(unit.borrow().0.clone());
+        }
+
+        Self::from_units(units)
+    }
+
+    pub fn from_units(definitions: Vec<Unit>) -> Result<Self, Error> {
+        let mut units = HashMap::with_capacity(definitions.len());
+
+        for mut unit in definitions {
+            if units.contains_key(&unit.unit_type) {
+                return Err(Error::DuplicateUnitDefinition(unit.unit_type));
+            }
+
+            unit.fill_default_animations();
+            units.insert(unit.unit_type, unit);
+        }
+
+        if let Some(missing) = UnitType::ALL
+            .iter()
+            .find(|unit_type| !units.contains_key(unit_type))
+        {
+            return Err(Error::MissingUnitDefinition(*missing));
+        }
+
+        Ok(UnitRegistry { units })
+    }
+
+    pub fn get(&self, unit_type: UnitType) -> &Unit {
+        &self.units[&unit_type]
+    }
+
+    /// Creates a fresh unit from its definition, with the stats of the given rank.
+    pub fn create(&self, unit_type: UnitType, rank: Rank, tile_position: na::Point2<i32>) -> Unit {
+        let mut unit = self.get(unit_type).clone();
+
+        unit.set_rank(rank.min(unit.max_rank()));
+        unit.tile_position = tile_position;
+        unit.cooldown = unit.attack_cooldown();
+
+        unit
+    }
+}
+
+/// Every mob definition found under `/mobs`, keyed by its file name.
+pub struct MobRegistry {
+    mobs: HashMap<String, MobDefinition>,
+}
+
+impl MobRegistry {
+    pub fn load(ctx: &mut ggez::Context, store: &mut resources::Store) -> Result<Self, Error> {
+        let paths = resources::find_ron_files(ctx, "/mobs").map_err(Error::GgezError)?;
+        let mut mobs = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let id = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().into_owned(),
+                None => continue,
+            };
+
+            let mob = store
+                .get::<resources::MobDefinition>(&resources::Key::from_path(&path), ctx)
+                .map_err(|e| Error::ResourceError(format!("{:?}", e)))?;
+
+            mobs.push((id, mob.borrow().0.clone()));
+        }
+
+        Self::from_mobs(mobs)
+    }
+
+    /// Mobs are identified by their file name, so two files with the same name are an error.
+    pub fn from_mobs(definitions: Vec<(String, MobDefinition)>) -> Result<Self, Error> {
+        let mut mobs = HashMap::with_capacity(definitions.len());
+
+        for (id, mob) in definitions {
+            if mobs.contains_key(&id) {
+                return Err(Error::DuplicateMobDefinition(id));
+            }
+
+            mobs.insert(id, mob);
+        }
+
+        Ok(MobRegistry { mobs })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&MobDefinition> {
+        self.mobs.get(id)
+    }
+
+    /// All regular mob ids, sorted so they can be indexed deterministically.
+    pub fn ids(&self) -> Vec<&str> {
+        self.sorted_ids(false)
+    }
+
+    pub fn boss_ids(&self) -> Vec<&str> {
+        self.sorted_ids(true)
+    }
+
+    fn sorted_ids(&self, boss: bool) -> Vec<&str> {
+        let mut ids: Vec<&str> = self
+            .mobs
+            .iter()
+            .filter(|(_, mob)| mob.boss == boss)
+            .map(|(id, _)| id.as_str())
+            .collect();
+
+        ids.sort();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::animation::AnimationState;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn requires_every_unit_type() {
+        let mut definitions: Vec<Unit> = UnitType::ALL
+            .iter()
+            .map(|unit_type| Unit {
+                unit_type: *unit_type,
+                ..Unit::default()
+            })
+            .collect();
+
+        definitions.retain(|unit| unit.unit_type != UnitType::Sage);
+
+        match UnitRegistry::from_units(definitions) {
+            Err(Error::MissingUnitDefinition(UnitType::Sage)) => {}
+            _ => panic!("expected a missing Sage definition"),
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_definitions() {
+        let mut definitions: Vec<Unit> = UnitType::ALL
+            .iter()
+            .map(|unit_type| Unit {
+                unit_type: *unit_type,
+                ..Unit::default()
+            })
+            .collect();
+
+        definitions.push(Unit {
+            unit_type: UnitType::Sage,
+            ..Unit::default()
+        });
+
+        match UnitRegistry::from_units(definitions) {
+            Err(Error::DuplicateUnitDefinition(UnitType::Sage)) => {}
+            _ => panic!("expected a duplicate Sage definition"),
+        }
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/mobs/slime.ron");
+        let slime: MobDefinition =
+            ron::de::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        match MobRegistry::from_mobs(vec![
+            ("slime".to_owned(), slime.clone()),
+            ("slime".to_owned(), slime),
+        ]) {
+            Err(Error::DuplicateMobDefinition(ref id)) if id == "slime" => {}
+            _ => panic!("expected a duplicate slime definition"),
+        }
+    }
+
+    #[test]
+    fn shipped_definitions_animate() {
+        for path in ron_files("resources/mobs") {
+            let mob: MobDefinition =
+                ron::de::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+            for state in &[
+                AnimationState::Idle,
+                AnimationState::Walk,
+                AnimationState::Death,
+            ] {
+                assert!(
+                    mob.animations.contains_key(state),
+                    "{:?} has no {:?}",
+                    path,
+                    state
+                );
+            }
+
+            let walk = &mob.animations[&AnimationState::Walk];
+            let walk_sprites: Vec<i32> = walk.frames.iter().map(|frame| frame.sprite_id).collect();
+
+            assert!(!walk.directions.is_empty());
+            assert!(
+                mob.animations[&AnimationState::Death]
+                    .frames
+                    .iter()
+                    .all(|frame| !walk_sprites.contains(&frame.sprite_id)),
+                "{:?} dies with its walk frames",
+                path
+            );
+        }
+
+        let units = ron_files("resources/units")
+            .iter()
+            .map(|path| ron::de::from_str(&std::fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+        let registry = UnitRegistry::from_units(units).unwrap();
+
+        for unit_type in UnitType::ALL.iter() {
+            let unit = registry.get(*unit_type);
+
+            assert!(unit.animations.contains_key(&AnimationState::Idle));
+            assert_eq!(
+                unit.attacks,
+                unit.animations.contains_key(&AnimationState::Attack)
+            );
+            // Recipe book icons show the sprite units rest on when placed.
+            assert_eq!(unit_type.sprite_id(), unit.current_sprite_id());
+        }
+    }
+
+    fn ron_files(directory: &str) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut directories = vec![Path::new(env!("CARGO_MANIFEST_DIR")).join(directory)];
+
+        while let Some(directory) = directories.pop() {
+            for entry in std::fs::read_dir(directory).unwrap() {
+                let path = entry.unwrap().path();
+
+                if path.is_dir() {
+                    directories.push(path);
+                } else if path
+                    .extension()
+                    .map_or(false, |extension| extension == "ron")
+                {
+                    files.push(path);
+                }
+            }
+        }
+
+        assert!(!files.is_empty());
+        files
+    }
+}
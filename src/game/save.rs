@@ -0,0 +1,158 @@
+use std::io::Write;
+
+use ggez::nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::game::board::Board;
+use crate::game::drop_table::{DropTableBracket, RoundRolls};
+use crate::game::unit::{Rank, TargetPriority, UnitType};
+use crate::types::Error;
+
+/// Saves live in the user directory, next to ggez's own config.
+const SAVE_PATH: &str = "/save.ron";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedUnit {
+    pub unit_type: UnitType,
+    pub rank: Rank,
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub target_priority: TargetPriority,
+}
+
+/// Enough of a game to pick it back up during the build phase of `round`.
+/// A save made mid-wave restarts that wave when loaded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub seed: u64,
+    pub round: u32,
+    /// Units rolled so far this round, so a seeded game rolls the same units after loading.
+    pub round_rolls: u32,
+    pub placed_units: u32,
+    pub gold: u32,
+    pub lives: u32,
+    pub units: Vec<SavedUnit>,
+}
+
+impl SaveGame {
+    pub fn exists(ctx: &ggez::Context) -> bool {
+        ggez::filesystem::exists(ctx, SAVE_PATH)
+    }
+
+    pub fn load(ctx: &mut ggez::Context) -> Result<Self, Error> {
+        let file = ggez::filesystem::open(ctx, SAVE_PATH).map_err(Error::GgezError)?;
+
+        ron::de::from_reader(file).map_err(Error::DeserializeError)
+    }
+
+    pub fn save(&self, ctx: &mut ggez::Context) -> Result<(), Error> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(Error::SerializeError)?;
+        let mut file = ggez::filesystem::create(ctx, SAVE_PATH).map_err(Error::GgezError)?;
+
+        file.write_all(ron.as_bytes()).map_err(Error::IoError)
+    }
+
+    /// The round's rolls as they stood when saved, replayed from the seed.
+    pub fn replay_rolls(&self, bracket: &DropTableBracket) -> RoundRolls {
+        let mut rolls = RoundRolls::new(self.seed, self.round);
+
+        for _ in 0..self.round_rolls {
+            rolls.roll(bracket);
+        }
+
+        rolls
+    }
+
+    pub fn saved_units(board: &Board) -> Vec<SavedUnit> {
+        board
+            .tiles
+            .iter()
+            .map(|unit| SavedUnit {
+                unit_type: unit.unit_type,
+                rank: unit.rank,
+                x: unit.tile_position.x,
+                y: unit.tile_position.y,
+                target_priority: unit.target_priority,
+            })
+            .collect()
+    }
+}
+
+impl SavedUnit {
+    pub fn tile_position(&self) -> na::Point2<i32> {
+        na::Point2::new(self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn saves_survive_a_round_trip() {
+        let save = make_save(3);
+        let ron = ron::ser::to_string(&save).unwrap();
+
+        assert_eq!(save, ron::de::from_str::<SaveGame>(&ron).unwrap());
+    }
+
+    #[test]
+    fn replayed_rolls_give_the_same_units() {
+        let mut bracket = DropTableBracket {
+            from_round: 1,
+            unit_types: HashMap::new(),
+            ranks: HashMap::new(),
+        };
+
+        for (unit_type, weight) in &[
+            (UnitType::Warrior, 2.0),
+            (UnitType::Mage, 1.0),
+            (UnitType::Archer, 1.0),
+        ] {
+            bracket.unit_types.insert(*unit_type, *weight);
+        }
+
+        for (rank, weight) in &[(1, 3.0), (2, 1.0)] {
+            bracket.ranks.insert(*rank, *weight);
+        }
+
+        let mut rolls = RoundRolls::new(7, 4);
+
+        for _ in 0..3 {
+            rolls.roll(&bracket);
+        }
+
+        let mut save = make_save(rolls.count());
+        save.seed = 7;
+        save.round = 4;
+
+        let mut replayed = save.replay_rolls(&bracket);
+
+        assert_eq!(3, replayed.count());
+
+        for _ in 0..10 {
+            assert_eq!(rolls.roll(&bracket), replayed.roll(&bracket));
+        }
+    }
+
+    fn make_save(round_rolls: u32) -> SaveGame {
+        SaveGame {
+            seed: 42,
+            round: 2,
+            round_rolls,
+            placed_units: 1,
+            gold: 15,
+            lives: 18,
+            units: vec![SavedUnit {
+                unit_type: UnitType::Archer,
+                rank: 2,
+                x: 10,
+                y: 12,
+                target_priority: TargetPriority::Strongest,
+            }],
+        }
+    }
+}
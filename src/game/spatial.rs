@@ -0,0 +1,107 @@
+use ggez::nalgebra as na;
+
+/// A uniform grid of buckets over world space, holding indices into some other list.
+/// Rebuilt from scratch whenever the positions move, which is cheap compared to
+/// checking every pair of things against each other.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(width: f32, height: f32, cell_size: f32) -> Self {
+        let columns = (width / cell_size).ceil().max(1.0) as usize;
+        let rows = (height / cell_size).ceil().max(1.0) as usize;
+
+        SpatialGrid {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+        }
+    }
+
+    /// Positions outside the grid are clamped into the border cells.
+    fn cell(&self, x: f32, y: f32) -> (usize, usize) {
+        let column = (x / self.cell_size).floor().max(0.0) as usize;
+        let row = (y / self.cell_size).floor().max(0.0) as usize;
+
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, position: &na::Point2<f32>) {
+        let (column, row) = self.cell(position.x, position.y);
+        self.cells[row * self.columns + column].push(index);
+    }
+
+    /// Replaces the contents of the grid, using each position's place in the iterator as its index.
+    pub fn rebuild<I>(&mut self, positions: I)
+    where
+        I: Iterator<Item = na::Point2<f32>>,
+    {
+        self.clear();
+
+        for (index, position) in positions.enumerate() {
+            self.insert(index, &position);
+        }
+    }
+
+    /// Indices in every cell touched by the square around the circle. This can include
+    /// things slightly out of range, so callers still need to check the real distance.
+    pub fn query<'a>(
+        &'a self,
+        center: &na::Point2<f32>,
+        radius: f32,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let (min_column, min_row) = self.cell(center.x - radius, center.y - radius);
+        let (max_column, max_row) = self.cell(center.x + radius, center.y + radius);
+
+        (min_row..=max_row)
+            .flat_map(move |row| {
+                (min_column..=max_column).map(move |column| row * self.columns + column)
+            })
+            .flat_map(move |cell| self.cells[cell].iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nearby_positions() {
+        let mut grid = SpatialGrid::new(640.0, 640.0, 32.0);
+        grid.rebuild(
+            vec![
+                na::Point2::new(10.0, 10.0),
+                na::Point2::new(40.0, 10.0),
+                na::Point2::new(600.0, 600.0),
+            ]
+            .into_iter(),
+        );
+
+        let mut found: Vec<usize> = grid.query(&na::Point2::new(20.0, 20.0), 24.0).collect();
+        found.sort();
+
+        assert_eq!(vec![0, 1], found);
+    }
+
+    #[test]
+    fn clamps_positions_outside_the_grid() {
+        let mut grid = SpatialGrid::new(64.0, 64.0, 32.0);
+        grid.rebuild(vec![na::Point2::new(-20.0, 100.0)].into_iter());
+
+        let found: Vec<usize> = grid.query(&na::Point2::new(0.0, 63.0), 1.0).collect();
+
+        assert_eq!(vec![0], found);
+    }
+}
@@ -1,11 +1,26 @@
-use std::time::Instant;
-
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use ggez::nalgebra as na;
 
-type Rank = u16;
+use crate::game::animation::{
+    Animation, AnimationSet, AnimationState, Animator, Direction, LoopMode, DEFAULT_FRAME_DURATION,
+};
+use crate::game::mob::MovementClass;
+
+pub type Rank = u16;
+
+/// Sprites every attacking unit shares until unit types get art of their own.
+const UNIT_SPRITE: i32 = 199;
+const ATTACK_SPRITE: i32 = 200;
+const ATTACK_UP_SPRITE: i32 = 198;
+/// Walls never attack, so they keep the plain tile units used to be drawn with.
+const WALL_SPRITE: i32 = 5;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Hash)]
+const ATTACK_FRAME_DURATION: f32 = 0.1;
+
+/// Absorbs the rounding error from adding up tick lengths, so timers end on the tick they should.
+const TIMER_EPSILON: f32 = 1e-4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, Hash)]
 pub enum UnitType {
     Warrior,
     Mage,
@@ -29,7 +44,134 @@ pub enum UnitType {
     Wall,
 }
 
-#[derive(Debug, Deserialize, Copy, Clone)]
+impl UnitType {
+    pub const ALL: [UnitType; 20] = [
+        UnitType::Warrior,
+        UnitType::Mage,
+        UnitType::Cleric,
+        UnitType::Ranger,
+        UnitType::Scout,
+        UnitType::Knight,
+        UnitType::DarkKnight,
+        UnitType::HeavyKnight,
+        UnitType::Android,
+        UnitType::Sorcerer,
+        UnitType::Geomancer,
+        UnitType::MagicKnight,
+        UnitType::Clergy,
+        UnitType::Sage,
+        UnitType::Celestial,
+        UnitType::Archer,
+        UnitType::Gunner,
+        UnitType::Gunslinger,
+        UnitType::Valkyrie,
+        UnitType::Wall,
+    ];
+
+    /// The sprite units of this type rest on, which is also how they are shown off the board.
+    pub fn sprite_id(self) -> i32 {
+        match self {
+            UnitType::Wall => WALL_SPRITE,
+            _ => UNIT_SPRITE,
+        }
+    }
+
+    /// Animations for every state a definition doesn't animate itself.
+    pub fn default_animations(self) -> AnimationSet {
+        let mut animations = AnimationSet::new();
+        animations.insert(
+            AnimationState::Idle,
+            Animation::from_sprites(&[self.sprite_id()], DEFAULT_FRAME_DURATION),
+        );
+
+        if self != UnitType::Wall {
+            let mut attack =
+                Animation::from_sprites(&[ATTACK_SPRITE, UNIT_SPRITE], ATTACK_FRAME_DURATION);
+            let facing_up =
+                Animation::from_sprites(&[ATTACK_UP_SPRITE, UNIT_SPRITE], ATTACK_FRAME_DURATION);
+
+            attack.loop_mode = LoopMode::Once;
+            attack.directions.insert(Direction::Up, facing_up.frames);
+            animations.insert(AnimationState::Attack, attack);
+        }
+
+        animations
+    }
+}
+
+/// Which kinds of mobs a unit is able to attack.
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
+pub enum Targets {
+    Ground,
+    Air,
+    Both,
+}
+
+impl Default for Targets {
+    fn default() -> Self {
+        Targets::Ground
+    }
+}
+
+impl Targets {
+    pub fn can_hit(self, movement: MovementClass) -> bool {
+        match (self, movement) {
+            (Targets::Both, _) => true,
+            (Targets::Ground, MovementClass::Ground) => true,
+            (Targets::Air, MovementClass::Flying) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Which of a mob's defenses a unit's attacks go against.
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum DamageType {
+    Physical,
+    Magical,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        DamageType::Physical
+    }
+}
+
+/// Which mob a unit attacks when several are in range.
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq)]
+pub enum TargetPriority {
+    First,
+    Last,
+    Strongest,
+    Weakest,
+}
+
+impl Default for TargetPriority {
+    fn default() -> Self {
+        TargetPriority::First
+    }
+}
+
+impl TargetPriority {
+    pub fn next(self) -> Self {
+        match self {
+            TargetPriority::First => TargetPriority::Last,
+            TargetPriority::Last => TargetPriority::Strongest,
+            TargetPriority::Strongest => TargetPriority::Weakest,
+            TargetPriority::Weakest => TargetPriority::First,
+        }
+    }
+}
+
+/// Stats a unit takes on once it reaches a given rank.
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
+pub struct RankStats {
+    pub range: f32,
+    pub damage: u32,
+    pub attack_speed: f32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
 pub struct Unit {
     pub range: f32,
     pub damage: u32,
@@ -38,11 +180,47 @@ pub struct Unit {
     pub rank: Rank,
     pub attacks: bool,
 
+    #[serde(default = "Unit::default_cost")]
+    pub cost: u32,
+
+    #[serde(default)]
+    pub targets: Targets,
+
+    #[serde(default)]
+    pub damage_type: DamageType,
+
+    #[serde(default)]
+    pub target_priority: TargetPriority,
+
+    /// Stats for each rank, starting at rank 1.
+    #[serde(default)]
+    pub ranks: Vec<RankStats>,
+
+    /// Filled in from `UnitType::default_animations` for the states left out.
+    #[serde(default)]
+    pub animations: AnimationSet,
+
+    #[serde(skip)]
+    pub animator: Animator,
+
+    /// Mobs finished off by this unit.
+    #[serde(skip)]
+    pub kills: u32,
+
+    /// Health taken off mobs by this unit.
+    #[serde(skip)]
+    pub damage_dealt: u64,
+
     #[serde(skip, default = "Unit::default_position")]
     pub tile_position: na::Point2::<i32>,
 
-    #[serde(skip, default = "Instant::now")]
-    pub last_attacked: Instant,
+    /// Seconds of game time until the unit can attack again.
+    #[serde(skip)]
+    pub cooldown: f32,
+
+    /// Seconds of game time the unit stays disabled for.
+    #[serde(skip)]
+    pub disabled_for: f32,
 }
 
 impl Default for Unit {
@@ -54,7 +232,17 @@ impl Default for Unit {
             unit_type: UnitType::Wall,
             rank: 1,
             attacks: false,
-            last_attacked: Instant::now(),
+            cost: Unit::default_cost(),
+            targets: Targets::default(),
+            damage_type: DamageType::default(),
+            target_priority: TargetPriority::default(),
+            ranks: Vec::new(),
+            animations: AnimationSet::new(),
+            animator: Animator::default(),
+            kills: 0,
+            damage_dealt: 0,
+            cooldown: 0.0,
+            disabled_for: 0.0,
             tile_position: na::Point2::new(0, 0),
         }
     }
@@ -66,25 +254,114 @@ impl Unit {
         Unit::default()
     }
 
-    pub fn attack_speed(&self) -> u128 {
-        (((1.0 / self.attack_speed) * 1000.0) as u32).into()
+    /// Adds the unit type's default animation for every state the definition leaves out.
+    pub fn fill_default_animations(&mut self) {
+        for (state, animation) in self.unit_type.default_animations() {
+            self.animations.entry(state).or_insert(animation);
+        }
+    }
+
+    /// Seconds of game time between attacks.
+    pub fn attack_cooldown(&self) -> f32 {
+        1.0 / self.attack_speed
     }
 
     pub fn check_attack(&self) -> Option<u32> {
-        let attack_speed = self.attack_speed();
+        if !self.attacks || self.is_disabled() {
+            return None;
+        }
 
-        if self.last_attacked.elapsed().as_millis() >= attack_speed {
-            return Some(self.damage);
+        if self.cooldown <= TIMER_EPSILON {
+            Some(self.damage)
         } else {
-            return None;
+            None
         }
     }
 
+    /// Counts the attack cooldown and any disable down by one tick of game time.
+    pub fn tick(&mut self, dt: f32) {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        self.disabled_for = (self.disabled_for - dt).max(0.0);
+    }
+
+    /// Attacks per second of game time at a given tick rate. Attacks can only happen
+    /// on a tick, so each cooldown is rounded up to a whole number of ticks.
+    pub fn effective_attack_speed(&self, ticks_per_second: f32) -> f32 {
+        if !self.attacks || self.is_disabled() {
+            return 0.0;
+        }
+
+        let cooldown = self.attack_cooldown() - TIMER_EPSILON;
+        let ticks = (cooldown * ticks_per_second).ceil().max(1.0);
+
+        ticks_per_second / ticks
+    }
+
+    pub fn max_rank(&self) -> Rank {
+        self.ranks.len() as Rank
+    }
+
+    /// Applies the stats defined for `rank`, returning false if the unit has no such rank.
+    pub fn set_rank(&mut self, rank: Rank) -> bool {
+        match rank
+            .checked_sub(1)
+            .and_then(|index| self.ranks.get(index as usize))
+        {
+            Some(stats) => {
+                self.range = stats.range;
+                self.damage = stats.damage;
+                self.attack_speed = stats.attack_speed;
+                self.rank = rank;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_for > TIMER_EPSILON
+    }
+
+    /// Keeps the unit from attacking for `duration` seconds of game time.
+    pub fn disable(&mut self, duration: f32) {
+        self.disabled_for = self.disabled_for.max(duration);
+    }
+
     pub fn perform_attack(&mut self) {
-        self.last_attacked = Instant::now();
+        self.cooldown = self.attack_cooldown();
+        self.animator.play(AnimationState::Attack);
+    }
+
+    /// Advances the animation, going back to idle once an attack has played out.
+    pub fn update_animation(&mut self, dt: f32) {
+        self.animator.update(dt);
+
+        if self.animator.state() == AnimationState::Attack
+            && self.animator.is_finished(&self.animations)
+        {
+            self.animator.set_state(AnimationState::Idle);
+        }
+    }
+
+    pub fn current_sprite_id(&self) -> i32 {
+        self.animator
+            .sprite_id(&self.animations)
+            .unwrap_or_else(|| self.unit_type.sprite_id())
+    }
+
+    pub fn record_hit(&mut self, damage: u32, killed: bool) {
+        self.damage_dealt += u64::from(damage);
+
+        if killed {
+            self.kills += 1;
+        }
     }
 
     fn default_position() -> na::Point2::<i32> {
         na::Point2::new(0, 0)
     }
+
+    fn default_cost() -> u32 {
+        10
+    }
 }
@@ -1,15 +1,28 @@
 //! Typedefs for input shortcuts.
 use ggez::event::*;
 use ggez_goodies::input;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Button {
         Select,
+        Sell,
+        Move,
+        Merge,
+        Cancel,
+        StartRound,
+        SpeedUp,
+        CycleTargeting,
+        Pan,
+        Recipes,
+        Paths,
+        Debug,
+        Navigation,
         Menu,
         Quit,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Axis {
         Vert,
         Horz,
@@ -27,19 +40,13 @@ pub struct MouseEvent {
 pub enum InputEvent {
         InputEffect(input::InputEffect<Axis, Button>),
         MouseEffect(MouseEvent),
+        Text(char),
+        /// Vertical scroll amount, positive when scrolling up.
+        MouseWheel(f32),
+        /// Unresolved key presses, only sent while `World::capturing_input` is set.
+        RawKey(KeyCode),
+        RawMouse(MouseButton),
 }
 
-pub type Binding = input::InputBinding<Axis, Button>;
 pub type Event = InputEvent;
 pub type State = input::InputState<Axis, Button>;
-
-/// Create the default keybindings for our input state.
-pub fn create_input_binding() -> input::InputBinding<Axis, Button> {
-        input::InputBinding::new()
-                .bind_key_to_axis(KeyCode::Up, Axis::Vert, true)
-                .bind_key_to_axis(KeyCode::Down, Axis::Vert, false)
-                .bind_key_to_axis(KeyCode::Left, Axis::Horz, false)
-                .bind_key_to_axis(KeyCode::Right, Axis::Horz, true)
-                .bind_key_to_button(KeyCode::Z, Button::Menu)
-                .bind_key_to_button(KeyCode::Escape, Button::Quit)
-}
@@ -0,0 +1,16 @@
+//! The game itself, kept separate from the main loop in `main.rs`
+//! so benchmarks can get at it too.
+
+pub mod bindings;
+pub mod camera;
+pub mod game;
+pub mod input;
+pub mod render;
+pub mod resources;
+pub mod scenes;
+pub mod screen;
+pub mod settings;
+pub mod spritesheet;
+pub mod types;
+pub mod util;
+pub mod world;
@@ -7,19 +7,11 @@ use std::path;
 use ggez::nalgebra as na;
 use ggez::{self, *};
 
-mod game;
-mod input;
-mod resources;
-mod scenes;
-mod screen;
-mod spritesheet;
-mod types;
-mod util;
-mod world;
+use ggez_game_template::settings::Settings;
+use ggez_game_template::{input, scenes, util, world};
 
 struct MainState {
     scenes: scenes::Stack,
-    input_binding: input::Binding,
 }
 
 impl MainState {
@@ -27,23 +19,20 @@ impl MainState {
         let world = world::World::new(ctx, resource_path);
         let mut scenestack = scenes::Stack::new(ctx, world);
         let initial_scene = Box::new(scenes::menu::MenuScene::new(ctx, &mut scenestack.world));
-        let level_scene = Box::new(scenes::level::LevelScene::new(ctx, &mut scenestack.world));
 
-        scenestack.push(level_scene);
         scenestack.push(initial_scene);
 
-        Self {
-            input_binding: input::create_input_binding(),
-            scenes: scenestack,
-        }
+        Self { scenes: scenestack }
     }
 }
 
 impl event::EventHandler for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        const DESIRED_FPS: u32 = 60;
+        let updates_per_second = self.scenes.world.updates_per_second();
+
+        self.scenes.world.events.clear();
 
-        while timer::check_update_time(ctx, DESIRED_FPS) {
+        while timer::check_update_time(ctx, updates_per_second) {
             self.scenes.update(ctx);
         }
 
@@ -81,7 +70,12 @@ impl event::EventHandler for MainState {
         _keymod: event::KeyMods,
         _repeat: bool,
     ) {
-        if let Some(ev) = self.input_binding.resolve(keycode) {
+        if self.scenes.world.capturing_input {
+            self.scenes.input(input::InputEvent::RawKey(keycode), true);
+            return;
+        }
+
+        if let Some(ev) = self.scenes.world.bindings.resolve_key(keycode) {
             self.scenes.world.input.update_effect(ev, true);
             self.scenes.input(input::InputEvent::InputEffect(ev), false);
         }
@@ -93,7 +87,7 @@ impl event::EventHandler for MainState {
         keycode: event::KeyCode,
         _keymod: event::KeyMods,
     ) {
-        if let Some(ev) = self.input_binding.resolve(keycode) {
+        if let Some(ev) = self.scenes.world.bindings.resolve_key(keycode) {
             self.scenes.world.input.update_effect(ev, false);
             self.scenes.input(input::InputEvent::InputEffect(ev), false);
         }
@@ -106,10 +100,13 @@ impl event::EventHandler for MainState {
         _x: f32,
         _y: f32,
     ) {
-        // @TODO(vy): ggez-goodies does not bind button events.
-        if event::MouseButton::Left == button {
-            let ev = ggez_goodies::input::InputEffect::Button(input::Button::Select);
+        if self.scenes.world.capturing_input {
+            self.scenes.input(input::InputEvent::RawMouse(button), true);
+            return;
+        }
 
+        // ggez-goodies only binds keys, so mouse buttons go through our own bindings.
+        if let Some(ev) = self.scenes.world.bindings.resolve_mouse(button) {
             self.scenes.world.input.update_effect(ev, true);
             self.scenes.input(input::InputEvent::InputEffect(ev), true);
         }
@@ -122,20 +119,31 @@ impl event::EventHandler for MainState {
         _x: f32,
         _y: f32,
     ) {
-        if event::MouseButton::Left == button {
-            let ev = ggez_goodies::input::InputEffect::Button(input::Button::Select);
-
+        if let Some(ev) = self.scenes.world.bindings.resolve_mouse(button) {
             self.scenes.world.input.update_effect(ev, false);
             self.scenes.input(input::InputEvent::InputEffect(ev), false);
         }
     }
 
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) {
+        self.scenes.input(input::InputEvent::MouseWheel(y), false);
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        self.scenes.input(input::InputEvent::Text(character), false);
+    }
+
     fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
+        // Positions arrive in drawable pixels, while everything else works in logical ones.
+        let screen = &self.scenes.world.screen;
+        let position = screen.to_logical_coordinate(na::Point2::new(x, y));
+        let delta = screen.to_logical_coordinate(na::Point2::new(dx, dy));
+
         let ev = input::MouseEvent {
-            x: x,
-            y: y,
-            dx: dx,
-            dy: dy,
+            x: position.x,
+            y: position.y,
+            dx: delta.x,
+            dy: delta.y,
         };
 
         self.scenes.input(input::InputEvent::MouseEffect(ev), false);
@@ -154,9 +162,13 @@ fn main() {
     };
     println!("Resource dir: {:?}", resource_dir);
 
+    // The saved settings are applied once the context exists; ggez picks up the
+    // window setup they wrote to its `conf.toml` on its own.
+    let settings = Settings::default();
+
     let cb = ContextBuilder::new("Tower", "Tower")
-        .window_setup(conf::WindowSetup::default().title("Tower").vsync(true))
-        .window_mode(conf::WindowMode::default().dimensions(1280.0, 1024.0))
+        .window_setup(settings.window_setup())
+        .window_mode(settings.window_mode())
         .add_resource_path(&resource_dir);
 
     let (ctx, ev) = &mut cb.build().unwrap();
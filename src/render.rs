@@ -0,0 +1,211 @@
+use std::cmp::Ordering;
+
+use ggez::graphics::{self, Drawable};
+use ggez::nalgebra as na;
+
+use crate::spritesheet::{SpriteLayer, Tile};
+
+/// What gets drawn over what, from the bottom up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenderLayer {
+    Terrain,
+    /// Flat markings on the ground, like paths and unit ranges.
+    Decals,
+    Units,
+    Mobs,
+    Projectiles,
+    Effects,
+    /// Screen space text and markers over the world. Panels drawn straight to the
+    /// screen after the queue is flushed end up above this.
+    Ui,
+}
+
+impl RenderLayer {
+    /// Entities overlap, so they are drawn from the top of the world down.
+    pub fn y_sorted(self) -> bool {
+        match self {
+            RenderLayer::Units | RenderLayer::Mobs | RenderLayer::Projectiles => true,
+            _ => false,
+        }
+    }
+}
+
+enum DrawCommand {
+    /// Batched with neighbouring sprites, in world space.
+    Sprite {
+        tile: Tile,
+        position: na::Point2<f32>,
+        color: graphics::Color,
+    },
+    Drawable(Box<dyn Drawable>, graphics::DrawParam),
+}
+
+struct Entry {
+    layer: RenderLayer,
+    depth: f32,
+    command: DrawCommand,
+}
+
+/// Collects a frame's drawing by layer and draws it in order once flushed.
+/// Sprites are batched for as long as nothing else needs to go in between.
+pub struct RenderQueue {
+    entries: Vec<Entry>,
+    sprites: SpriteLayer,
+}
+
+impl RenderQueue {
+    pub fn new(sprites: SpriteLayer) -> Self {
+        RenderQueue {
+            entries: Vec::new(),
+            sprites,
+        }
+    }
+
+    /// Queues a sprite with its top left corner at `position`, in world space.
+    pub fn sprite(
+        &mut self,
+        layer: RenderLayer,
+        tile: Tile,
+        position: na::Point2<f32>,
+        color: graphics::Color,
+    ) {
+        self.entries.push(Entry {
+            layer,
+            depth: position.y,
+            command: DrawCommand::Sprite {
+                tile,
+                position,
+                color,
+            },
+        });
+    }
+
+    /// Queues anything else. `depth` is the world y it is sorted by in y sorted layers,
+    /// so a health bar given its mob's depth stays with its mob.
+    pub fn draw<D, P>(&mut self, layer: RenderLayer, depth: f32, drawable: D, param: P)
+    where
+        D: Drawable + 'static,
+        P: Into<graphics::DrawParam>,
+    {
+        self.entries.push(Entry {
+            layer,
+            depth,
+            command: DrawCommand::Drawable(Box::new(drawable), param.into()),
+        });
+    }
+
+    /// Draws everything queued since the last flush. `sprite_param` places the
+    /// world space sprites on the screen.
+    pub fn flush(
+        &mut self,
+        ctx: &mut ggez::Context,
+        sprite_param: graphics::DrawParam,
+    ) -> ggez::GameResult<()> {
+        sort_entries(&mut self.entries);
+
+        let mut pending_sprites = false;
+
+        for entry in self.entries.drain(..) {
+            match entry.command {
+                DrawCommand::Sprite {
+                    tile,
+                    position,
+                    color,
+                } => {
+                    self.sprites
+                        .add_tinted(&tile, position.x, position.y, color);
+                    pending_sprites = true;
+                }
+                DrawCommand::Drawable(drawable, param) => {
+                    if pending_sprites {
+                        self.sprites.draw(ctx, sprite_param)?;
+                        self.sprites.clear();
+                        pending_sprites = false;
+                    }
+
+                    drawable.draw(ctx, param)?;
+                }
+            }
+        }
+
+        if pending_sprites {
+            self.sprites.draw(ctx, sprite_param)?;
+            self.sprites.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Orders entries by layer, then by depth in y sorted layers. The sort is stable,
+/// so anything else keeps the order it was queued in.
+fn sort_entries(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| {
+        a.layer.cmp(&b.layer).then_with(|| {
+            if a.layer.y_sorted() {
+                a.depth.partial_cmp(&b.depth).unwrap_or(Ordering::Equal)
+            } else {
+                Ordering::Equal
+            }
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(layer: RenderLayer, depth: f32, sprite_id: i32) -> Entry {
+        Entry {
+            layer,
+            depth,
+            command: DrawCommand::Sprite {
+                tile: Tile {
+                    sprite_layer: 0,
+                    sprite_id,
+                },
+                position: na::Point2::new(0.0, depth),
+                color: graphics::WHITE,
+            },
+        }
+    }
+
+    fn sprite_ids(entries: &[Entry]) -> Vec<i32> {
+        entries
+            .iter()
+            .map(|entry| match &entry.command {
+                DrawCommand::Sprite { tile, .. } => tile.sprite_id,
+                DrawCommand::Drawable(..) => -1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entries_sort_by_layer_then_depth() {
+        let mut entries = vec![
+            entry(RenderLayer::Effects, 0.0, 1),
+            entry(RenderLayer::Mobs, 30.0, 2),
+            entry(RenderLayer::Units, 50.0, 3),
+            entry(RenderLayer::Mobs, 10.0, 4),
+            entry(RenderLayer::Terrain, 0.0, 5),
+        ];
+
+        sort_entries(&mut entries);
+
+        assert_eq!(vec![5, 3, 4, 2, 1], sprite_ids(&entries));
+    }
+
+    #[test]
+    fn unsorted_layers_keep_their_order() {
+        let mut entries = vec![
+            entry(RenderLayer::Decals, 30.0, 1),
+            entry(RenderLayer::Decals, 10.0, 2),
+            entry(RenderLayer::Ui, 20.0, 3),
+            entry(RenderLayer::Ui, 0.0, 4),
+        ];
+
+        sort_entries(&mut entries);
+
+        assert_eq!(vec![1, 2, 3, 4], sprite_ids(&entries));
+    }
+}
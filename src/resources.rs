@@ -7,7 +7,7 @@ use ggez::{self, graphics};
 use log::*;
 use warmy;
 
-use crate::game::{mob, unit};
+use crate::game::{drop_table, mob, unit};
 use crate::types::Error;
 
 /// Again, because `warmy` assumes direct filesystem dirs
@@ -48,6 +48,28 @@ pub type Store = warmy::Store<ggez::Context, Key>;
 type Storage = warmy::Storage<ggez::Context, Key>;
 pub type Loaded<T> = warmy::Loaded<T, Key>;
 
+/// Recursively collects every `.ron` file under `dir`, using ggez's
+/// resource paths so the results can be used as `Key`s directly.
+pub fn find_ron_files<P>(ctx: &ggez::Context, dir: P) -> ggez::GameResult<Vec<path::PathBuf>>
+where
+    P: AsRef<path::Path>,
+{
+    let mut found = Vec::new();
+    let entries: Vec<path::PathBuf> = ggez::filesystem::read_dir(ctx, dir)?.collect();
+
+    for entry in entries {
+        if ggez::filesystem::is_dir(ctx, &entry) {
+            found.append(&mut find_ron_files(ctx, &entry)?);
+        } else if entry.extension().map_or(false, |ext| ext == "ron") {
+            found.push(entry);
+        }
+    }
+
+    found.sort();
+
+    Ok(found)
+}
+
 /// A wrapper for a ggez Image, so we can implement warmy's `Load` trait on it.
 #[derive(Debug, Clone)]
 pub struct Image(pub graphics::Image);
@@ -58,6 +80,9 @@ pub struct Unit(pub unit::Unit);
 #[derive(Debug)]
 pub struct MobDefinition(pub mob::MobDefinition);
 
+#[derive(Debug)]
+pub struct DropTables(pub drop_table::DropTables);
+
 /// And, here actually tell Warmy how to load things.
 impl warmy::Load<ggez::Context, Key> for Image {
     type Error = Error;
@@ -115,3 +140,23 @@ impl warmy::Load<ggez::Context, Key> for MobDefinition {
         }
     }
 }
+
+impl warmy::Load<ggez::Context, Key> for DropTables {
+    type Error = Error;
+    fn load(
+        key: Key,
+        _storage: &mut Storage,
+        ctx: &mut ggez::Context,
+    ) -> Result<Loaded<Self>, Self::Error> {
+        debug!("Loading drop tables {:?}", key);
+
+        match key {
+            Key::Path(path) => {
+                let file = ggez::filesystem::open(ctx, &path).map_err(|e| Error::GgezError(e))?;
+                let ron = ron::de::from_reader(file).map_err(|e| Error::DeserializeError(e))?;
+
+                Ok(warmy::Loaded::from(DropTables(ron)))
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use ggez;
+use ggez::nalgebra as na;
+use ggez_goodies::input::InputEffect;
+use ggez_goodies::scene;
+use log::*;
+
+use crate::bindings::{Action, Bindings, BoundInput, ACTIONS};
+use crate::input;
+use crate::scenes;
+use crate::scenes::menu_list::MenuList;
+use crate::world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ControlItem {
+    Bind(Action),
+    Reset,
+    Back,
+}
+
+/// Lists every action with its inputs. Selecting one waits for the next key or mouse
+/// button and binds it in its place.
+pub struct ControlsScene {
+    list: MenuList<ControlItem>,
+    /// The action waiting for an input, while one is being rebound.
+    rebinding: Option<Action>,
+    done: bool,
+}
+
+impl ControlsScene {
+    pub fn new(_ctx: &mut ggez::Context, world: &mut World) -> Self {
+        let items: Vec<ControlItem> = ACTIONS
+            .iter()
+            .map(|action| ControlItem::Bind(*action))
+            .chain(vec![ControlItem::Reset, ControlItem::Back])
+            .collect();
+
+        ControlsScene {
+            list: MenuList::new(world.font, &items).compact(),
+            rebinding: None,
+            done: false,
+        }
+    }
+
+    fn finish_rebinding(&mut self, world: &mut World, input: BoundInput) {
+        if let Some(action) = self.rebinding.take() {
+            world.bindings.rebind(action, input);
+        }
+
+        world.capturing_input = false;
+    }
+}
+
+fn label(item: ControlItem, bindings: &Bindings, rebinding: Option<Action>) -> String {
+    match item {
+        ControlItem::Bind(action) if rebinding == Some(action) => {
+            format!("{}: press a key...", action.label())
+        }
+        ControlItem::Bind(action) => {
+            let inputs: Vec<&str> = bindings
+                .inputs_for(action)
+                .iter()
+                .filter_map(|input| input.name())
+                .collect();
+
+            format!("{}: {}", action.label(), inputs.join(", "))
+        }
+        ControlItem::Reset => "Reset to Defaults".to_owned(),
+        ControlItem::Back => "Back".to_owned(),
+    }
+}
+
+impl scene::Scene<World, input::Event> for ControlsScene {
+    fn update(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> scenes::Switch {
+        if self.done {
+            if let Err(e) = gameworld.bindings.save(ctx) {
+                warn!("Could not save key bindings: {}", e);
+            }
+
+            scene::SceneSwitch::Pop
+        } else {
+            scene::SceneSwitch::None
+        }
+    }
+
+    fn draw(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let bindings = &gameworld.bindings;
+        let rebinding = self.rebinding;
+
+        self.list.draw(gameworld, ctx, "Controls", |item| {
+            label(item, bindings, rebinding)
+        })
+    }
+
+    fn name(&self) -> &str {
+        "ControlsScene"
+    }
+
+    fn input(&mut self, gameworld: &mut World, ev: input::Event, _started: bool) {
+        match ev {
+            input::InputEvent::RawKey(key) => {
+                self.finish_rebinding(gameworld, BoundInput::Key(key));
+            }
+            input::InputEvent::RawMouse(button) => {
+                self.finish_rebinding(gameworld, BoundInput::Mouse(button));
+            }
+            input::InputEvent::InputEffect(InputEffect::Axis(input::Axis::Vert, up)) => {
+                if gameworld.input.get_axis_raw(input::Axis::Vert) != 0.0 {
+                    self.list.move_selection(up);
+                }
+            }
+            input::InputEvent::InputEffect(_) => {
+                if gameworld.input.get_button_pressed(input::Button::Select) {
+                    match self.list.selected() {
+                        ControlItem::Bind(action) => {
+                            self.rebinding = Some(action);
+                            gameworld.capturing_input = true;
+                        }
+                        ControlItem::Reset => gameworld.bindings = Bindings::default(),
+                        ControlItem::Back => self.done = true,
+                    }
+                }
+
+                if gameworld.input.get_button_pressed(input::Button::Quit) {
+                    self.done = true;
+                }
+            }
+            input::InputEvent::MouseEffect(effect) => {
+                self.list
+                    .hover(gameworld, na::Point2::new(effect.x, effect.y));
+            }
+            _ => {}
+        }
+    }
+}
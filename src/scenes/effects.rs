@@ -0,0 +1,259 @@
+use ggez;
+use ggez::graphics;
+use ggez::nalgebra as na;
+
+use crate::game::events::CombatEvent;
+use crate::game::unit::DamageType;
+use crate::render::{RenderLayer, RenderQueue};
+use crate::screen::Transform;
+
+/// Seconds a damage number stays up, rising as it fades.
+const TEXT_LIFETIME: f32 = 0.8;
+/// How far a damage number rises, in world pixels.
+const TEXT_RISE: f32 = 12.0;
+const TEXT_SIZE: f32 = 14.0;
+/// Lost lives are called out louder than damage.
+const LEAK_TEXT_SIZE: f32 = 22.0;
+
+const FLASH_DURATION: f32 = 0.15;
+
+const PUFF_LIFETIME: f32 = 0.4;
+const PUFF_PARTICLES: usize = 8;
+/// How far puff particles travel, in world pixels.
+const PUFF_DISTANCE: f32 = 10.0;
+
+struct FloatingText {
+    text: String,
+    position: na::Point2<f32>,
+    color: graphics::Color,
+    size: f32,
+    age: f32,
+}
+
+struct Flash {
+    mob: u64,
+    remaining: f32,
+}
+
+struct Puff {
+    position: na::Point2<f32>,
+    age: f32,
+}
+
+/// Short lived combat feedback: damage numbers, hit flashes, death puffs and lost lives.
+/// Spawned from the board's combat events and aged along with the simulation.
+pub struct Effects {
+    font: graphics::Font,
+    texts: Vec<FloatingText>,
+    flashes: Vec<Flash>,
+    puffs: Vec<Puff>,
+}
+
+fn damage_color(damage_type: DamageType) -> graphics::Color {
+    match damage_type {
+        DamageType::Physical => graphics::Color::new(1.0, 0.9, 0.6, 1.0),
+        DamageType::Magical => graphics::Color::new(0.6, 0.7, 1.0, 1.0),
+    }
+}
+
+impl Effects {
+    pub fn new(font: graphics::Font) -> Self {
+        Effects {
+            font,
+            texts: Vec::new(),
+            flashes: Vec::new(),
+            puffs: Vec::new(),
+        }
+    }
+
+    pub fn handle(&mut self, event: &CombatEvent) {
+        match *event {
+            CombatEvent::MobDamaged {
+                mob,
+                position,
+                damage,
+                damage_type,
+            } => {
+                if damage == 0 {
+                    return;
+                }
+
+                self.texts.push(FloatingText {
+                    text: damage.to_string(),
+                    position,
+                    color: damage_color(damage_type),
+                    size: TEXT_SIZE,
+                    age: 0.0,
+                });
+
+                self.flashes.retain(|flash| flash.mob != mob);
+                self.flashes.push(Flash {
+                    mob,
+                    remaining: FLASH_DURATION,
+                });
+            }
+            CombatEvent::MobKilled { position, .. } => {
+                self.puffs.push(Puff { position, age: 0.0 });
+            }
+            CombatEvent::MobLeaked {
+                position,
+                life_cost,
+                ..
+            } => {
+                self.texts.push(FloatingText {
+                    text: format!("-{}", life_cost),
+                    position,
+                    color: graphics::Color::new(1.0, 0.3, 0.3, 1.0),
+                    size: LEAK_TEXT_SIZE,
+                    age: 0.0,
+                });
+            }
+            CombatEvent::UnitAttacked { .. } | CombatEvent::WaveCleared { .. } => {}
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for text in self.texts.iter_mut() {
+            text.age += dt;
+        }
+
+        for flash in self.flashes.iter_mut() {
+            flash.remaining -= dt;
+        }
+
+        for puff in self.puffs.iter_mut() {
+            puff.age += dt;
+        }
+
+        self.texts.retain(|text| text.age < TEXT_LIFETIME);
+        self.flashes.retain(|flash| flash.remaining > 0.0);
+        self.puffs.retain(|puff| puff.age < PUFF_LIFETIME);
+    }
+
+    /// How strongly a mob should be tinted from being hit, from 0 to 1.
+    pub fn flash(&self, mob: u64) -> f32 {
+        self.flashes
+            .iter()
+            .find(|flash| flash.mob == mob)
+            .map_or(0.0, |flash| flash.remaining / FLASH_DURATION)
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut ggez::Context,
+        transform: &Transform,
+        queue: &mut RenderQueue,
+    ) -> ggez::GameResult<()> {
+        if !self.puffs.is_empty() {
+            let mut builder = graphics::MeshBuilder::new();
+
+            for puff in &self.puffs {
+                let progress = puff.age / PUFF_LIFETIME;
+                let color = graphics::Color::new(0.8, 0.8, 0.8, 1.0 - progress);
+
+                for i in 0..PUFF_PARTICLES {
+                    let angle = i as f32 / PUFF_PARTICLES as f32 * std::f32::consts::PI * 2.0;
+                    let offset =
+                        na::Vector2::new(angle.cos(), angle.sin()) * PUFF_DISTANCE * progress;
+
+                    builder.circle(
+                        graphics::DrawMode::fill(),
+                        puff.position + offset,
+                        2.0 * (1.0 - progress) + 0.5,
+                        0.5,
+                        color,
+                    );
+                }
+            }
+
+            let mesh = builder.build(ctx)?;
+            queue.draw(RenderLayer::Effects, 0.0, mesh, transform.draw_param());
+        }
+
+        // Text is drawn in screen space, so it stays readable at any zoom.
+        for text in &self.texts {
+            let progress = text.age / TEXT_LIFETIME;
+            let position = transform
+                .world_to_screen(&(text.position - na::Vector2::new(0.0, TEXT_RISE * progress)));
+
+            let mut color = text.color;
+            color.a = 1.0 - progress;
+
+            let fragment = graphics::Text::new((text.text.as_str(), self.font, text.size));
+            let (width, height) = fragment.dimensions(ctx);
+
+            queue.draw(
+                RenderLayer::Effects,
+                0.0,
+                fragment,
+                (
+                    na::Point2::new(
+                        position.x - width as f32 / 2.0,
+                        position.y - height as f32 / 2.0,
+                    ),
+                    color,
+                ),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn damaged(mob: u64) -> CombatEvent {
+        CombatEvent::MobDamaged {
+            mob,
+            position: na::Point2::new(0.0, 0.0),
+            damage: 5,
+            damage_type: DamageType::Physical,
+        }
+    }
+
+    fn make_effects() -> Effects {
+        Effects {
+            font: graphics::Font::default(),
+            texts: Vec::new(),
+            flashes: Vec::new(),
+            puffs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn flash_fades_out() {
+        let mut effects = make_effects();
+        effects.handle(&damaged(3));
+
+        assert_eq!(1.0, effects.flash(3));
+        assert_eq!(0.0, effects.flash(4));
+
+        effects.update(FLASH_DURATION / 2.0);
+        assert!((effects.flash(3) - 0.5).abs() < 0.001);
+
+        effects.update(FLASH_DURATION);
+        assert_eq!(0.0, effects.flash(3));
+        assert!(effects.flashes.is_empty());
+    }
+
+    #[test]
+    fn effects_expire() {
+        let mut effects = make_effects();
+        effects.handle(&damaged(1));
+        effects.handle(&CombatEvent::MobKilled {
+            mob: 1,
+            position: na::Point2::new(0.0, 0.0),
+            bounty: 1,
+        });
+
+        assert_eq!(1, effects.texts.len());
+        assert_eq!(1, effects.puffs.len());
+
+        effects.update(TEXT_LIFETIME);
+
+        assert!(effects.texts.is_empty());
+        assert!(effects.puffs.is_empty());
+    }
+}
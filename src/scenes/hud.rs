@@ -0,0 +1,205 @@
+use ggez;
+use ggez::graphics;
+use ggez::nalgebra as na;
+
+use crate::game::events::CombatEvent;
+
+const WIDTH: f32 = 220.0;
+const LINE_HEIGHT: f32 = 22.0;
+const MARGIN: f32 = 8.0;
+/// Seconds a round cleared banner stays up.
+const BANNER_DURATION: f32 = 2.5;
+
+/// The state of the level the HUD shows, gathered by the level every frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HudStatus {
+    pub phase: &'static str,
+    pub round: u32,
+    pub units_to_place: u32,
+    pub gold: u32,
+    pub lives: u32,
+    /// Tiles from the first waypoint to the last.
+    pub path_length: u32,
+    /// Real seconds until the next mobs of the round spawn, while some are still to come.
+    pub next_spawn: Option<f32>,
+    /// Mobs alive plus those still to spawn this round.
+    pub remaining_mobs: u32,
+}
+
+impl HudStatus {
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            self.phase.to_owned(),
+            format!("Round {}", self.round),
+            format!("Gold: {}", self.gold),
+            format!("Lives: {}", self.lives),
+            format!("Path: {} tiles", self.path_length),
+        ];
+
+        if self.units_to_place > 0 {
+            lines.push(format!("Units to place: {}", self.units_to_place));
+        }
+
+        if let Some(seconds) = self.next_spawn {
+            lines.push(format!("Next spawn in {:.1}s", seconds));
+        }
+
+        if self.remaining_mobs > 0 {
+            lines.push(format!("Mobs left: {}", self.remaining_mobs));
+        }
+
+        lines
+    }
+}
+
+/// Round and player info in the top right corner, plus banners for cleared rounds,
+/// drawn in screen space over the level.
+pub struct Hud {
+    font: graphics::Font,
+    /// A message across the top of the screen and the seconds it has left.
+    banner: Option<(String, f32)>,
+}
+
+impl Hud {
+    pub fn new(font: graphics::Font) -> Self {
+        Hud { font, banner: None }
+    }
+
+    pub fn handle(&mut self, event: &CombatEvent) {
+        if let CombatEvent::WaveCleared { round } = event {
+            self.banner = Some((format!("Round {} cleared!", round), BANNER_DURATION));
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if let Some((_, time_left)) = self.banner.as_mut() {
+            *time_left -= dt;
+        }
+
+        self.banner = self.banner.take().filter(|(_, time_left)| *time_left > 0.0);
+    }
+
+    pub fn banner(&self) -> Option<&str> {
+        self.banner.as_ref().map(|(message, _)| message.as_str())
+    }
+
+    pub fn draw(&self, ctx: &mut ggez::Context, status: &HudStatus) -> ggez::GameResult<()> {
+        let screen = graphics::screen_coordinates(ctx);
+        let lines = status.lines();
+
+        let panel = graphics::Rect::new(
+            screen.x + screen.w - WIDTH - MARGIN,
+            screen.y + MARGIN,
+            WIDTH,
+            lines.len() as f32 * LINE_HEIGHT + MARGIN * 2.0,
+        );
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            panel,
+            graphics::Color::new(0.0, 0.0, 0.0, 0.5),
+        )?;
+
+        graphics::draw(ctx, &background, graphics::DrawParam::default())?;
+
+        for (i, line) in lines.into_iter().enumerate() {
+            let text = graphics::Text::new((line, self.font, 18.0));
+
+            graphics::draw(
+                ctx,
+                &text,
+                (
+                    na::Point2::new(panel.x + MARGIN, panel.y + MARGIN + i as f32 * LINE_HEIGHT),
+                    graphics::WHITE,
+                ),
+            )?;
+        }
+
+        if let Some(message) = self.banner() {
+            let mut text = graphics::Text::new((message, self.font, 32.0));
+            text.set_bounds(na::Point2::new(screen.w, screen.h), graphics::Align::Center);
+
+            graphics::draw(
+                ctx,
+                &text,
+                (
+                    na::Point2::new(screen.x, screen.y + screen.h / 4.0),
+                    graphics::Color::new(1.0, 0.9, 0.4, 1.0),
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_skip_what_does_not_apply() {
+        let mut status = HudStatus {
+            phase: "Place units",
+            round: 3,
+            units_to_place: 2,
+            gold: 40,
+            lives: 17,
+            path_length: 80,
+            next_spawn: None,
+            remaining_mobs: 0,
+        };
+
+        assert_eq!(
+            vec![
+                "Place units",
+                "Round 3",
+                "Gold: 40",
+                "Lives: 17",
+                "Path: 80 tiles",
+                "Units to place: 2"
+            ],
+            status.lines()
+        );
+
+        status.units_to_place = 0;
+        status.next_spawn = Some(1.5);
+        status.remaining_mobs = 12;
+
+        assert_eq!(
+            vec![
+                "Place units",
+                "Round 3",
+                "Gold: 40",
+                "Lives: 17",
+                "Path: 80 tiles",
+                "Next spawn in 1.5s",
+                "Mobs left: 12"
+            ],
+            status.lines()
+        );
+    }
+
+    #[test]
+    fn cleared_waves_show_a_banner() {
+        let mut hud = Hud {
+            font: graphics::Font::default(),
+            banner: None,
+        };
+
+        hud.handle(&CombatEvent::MobLeaked {
+            mob: 1,
+            position: na::Point2::new(0.0, 0.0),
+            life_cost: 1,
+        });
+        assert_eq!(None, hud.banner());
+
+        hud.handle(&CombatEvent::WaveCleared { round: 4 });
+        hud.update(1.0);
+        assert_eq!(Some("Round 4 cleared!"), hud.banner());
+
+        hud.update(BANNER_DURATION);
+        assert_eq!(None, hud.banner());
+    }
+}
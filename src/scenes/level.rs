@@ -1,25 +1,53 @@
-use std::collections::HashMap;
-
-use droprate::{FairlyRandomTable, ProbabilityTable};
 use ggez;
 use ggez::graphics;
 use ggez::nalgebra as na;
 use ggez_goodies::scene;
-use rand::seq::SliceRandom;
+use log::*;
 use warmy;
 
+use crate::camera::Camera;
+use crate::game::board::{Board, PlacementError, BOARD_HEIGHT, BOARD_WIDTH};
+use crate::game::combination::{Combination, Merge};
+use crate::game::drop_table::RoundRolls;
 use crate::game::mob;
-use crate::game::unit;
+use crate::game::navigation::{FlowField, NavigationMode};
+use crate::game::save::SaveGame;
+use crate::game::unit::{Rank, Unit, UnitType};
 use crate::input;
+use crate::render::{RenderLayer, RenderQueue};
 use crate::resources;
 use crate::scenes;
+use crate::scenes::effects::Effects;
+use crate::scenes::hud::{Hud, HudStatus};
+use crate::scenes::path_overlay::PathOverlay;
+use crate::scenes::pause::PauseScene;
+use crate::scenes::recipe_book::RecipeBook;
+use crate::scenes::unit_panel::{PanelButton, UnitPanel};
+use crate::screen::{Transform, TILE_SIZE};
 use crate::spritesheet::{SpriteLayer, Tile, TileMap};
-use crate::world::World;
+use crate::world::{PauseAction, World, DESIRED_FPS, TICK_LENGTH};
+
+/// Every n-th round starts with a boss.
+const BOSS_ROUND_INTERVAL: u32 = 5;
+/// Units placed during each round's build phase.
+const UNITS_PER_ROUND: u32 = 5;
+/// How many times mobs spawn each round.
+const SPAWNS_PER_ROUND: u32 = 10;
+/// Ticks between spawns.
+const SPAWN_INTERVAL: u32 = 60;
 
-const SCALE_X: f32 = 1.5;
-const SCALE_Y: f32 = 1.5;
+/// Width and height of the island image, which the camera is kept within.
+const ISLAND_SIZE: f32 = 656.0;
+/// The island extends this far past the board on every side.
+const ISLAND_BORDER: f32 = 8.0;
+/// Camera speed in screen pixels per second, for the arrow keys and edge scrolling.
+const PAN_SPEED: f32 = 600.0;
+/// How close to the edge of the window the mouse has to be to scroll.
+const EDGE_SCROLL_MARGIN: f32 = 16.0;
+/// Zoom factor per mouse wheel step.
+const ZOOM_STEP: f32 = 1.1;
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LevelState {
     PickUnit,
     CombineUnit,
@@ -27,30 +55,78 @@ pub enum LevelState {
     RoundEnd,
 }
 
+impl LevelState {
+    pub fn label(self) -> &'static str {
+        match self {
+            LevelState::PickUnit => "Place units",
+            LevelState::CombineUnit => "Combine units",
+            LevelState::RoundStart => "Round in progress",
+            LevelState::RoundEnd => "Round over",
+        }
+    }
+}
+
 pub enum UserAction {
     BuildUnit,
+    SellUnit,
+    MoveUnit,
+    MergeUnit,
+    Panel(PanelButton),
+}
+
+/// What placing the next unit on a tile would look like.
+struct PlacementPreview {
+    tile: na::Point2<i32>,
+    /// The unit the next roll gives, if there is one.
+    unit: Option<Unit>,
+    paths: Result<Vec<na::Point2<i32>>, PlacementError>,
 }
 
 pub struct LevelScene {
     done: bool,
-    sprite_layer: SpriteLayer,
+    paused: bool,
+    render_queue: RenderQueue,
     bg: warmy::Res<resources::Image>,
     island: warmy::Res<resources::Image>,
-    chicken_definition: warmy::Res<resources::MobDefinition>,
     state: LevelState,
     placed_units: u32,
     spawned_mobs: u32,
     current_ticks: u32,
     current_user_action: Option<UserAction>,
     paths: Vec<na::Point2<i32>>,
-    hovered_tile: Option<na::Point2<u32>>,
-    drop_rate: FairlyRandomTable<u16>,
+    path_overlay: PathOverlay,
+    show_paths: bool,
+    checkpoints: Vec<na::Point2<i32>>,
+    flow_fields: Vec<FlowField>,
+    navigation_mode: NavigationMode,
+    hovered_tile: Option<na::Point2<i32>>,
+    mouse_position: Option<na::Point2<f32>>,
+    camera: Camera,
+    moving_unit: Option<na::Point2<i32>>,
+    /// The unit shown in the unit panel.
+    selected_unit: Option<na::Point2<i32>>,
+    placement_preview: Option<PlacementPreview>,
+    round: u32,
+    drop_tables: warmy::Res<resources::DropTables>,
+    seed: u64,
+    rolls: RoundRolls,
+    show_drop_odds: bool,
+    hud: Hud,
+    effects: Effects,
+    unit_panel: UnitPanel,
+    recipe_book: RecipeBook,
+    show_recipe_book: bool,
+    /// The recipe under the mouse, whose ingredients are highlighted on the board.
+    hovered_recipe: Option<UnitType>,
 }
 
 impl LevelScene {
-    pub fn new(ctx: &mut ggez::Context, world: &mut World) -> Self {
+    pub fn new(ctx: &mut ggez::Context, world: &mut World, seed: u64) -> Self {
         let done = false;
 
+        world.boards = vec![Board::default()];
+        world.fast_forward = false;
+
         let bg = world
             .resources
             .get::<resources::Image>(&resources::Key::from_path("/images/bg.png"), ctx)
@@ -69,50 +145,520 @@ impl LevelScene {
         let board = world.boards.get_mut(0).unwrap();
 
         let tilemap = TileMap::new(spritesheet, 16);
+        let recipe_book = RecipeBook::new(world.font, tilemap.clone());
+
+        let lanes = board.calculate_lane_paths().unwrap();
+        let paths = lanes.concat();
+        let checkpoints = board.checkpoints();
+        let flow_fields = board.calculate_flow_fields();
 
-        let chicken_definition = world
+        let drop_tables = world
             .resources
-            .get::<resources::MobDefinition>(&resources::Key::from_path("/mobs/chicken.ron"), ctx)
+            .get::<resources::DropTables>(
+                &resources::Key::from_path("/drop_tables/rounds.ron"),
+                ctx,
+            )
             .unwrap();
 
-        let paths = board.calculate_paths().unwrap();
-
-        let mut drop_rate_map = HashMap::new();
-
-        drop_rate_map.insert(1, 1.00);
-
-        let drop_rate = FairlyRandomTable::<u16>::from_map(drop_rate_map);
+        let round = 1;
 
         LevelScene {
             done,
+            paused: false,
             bg,
             island,
             paths,
-            chicken_definition,
+            path_overlay: PathOverlay::new(lanes),
+            show_paths: false,
+            checkpoints,
+            flow_fields,
+            navigation_mode: NavigationMode::default(),
             hovered_tile: None,
+            mouse_position: None,
+            camera: Camera::new(
+                graphics::Rect::new(-ISLAND_BORDER, -ISLAND_BORDER, ISLAND_SIZE, ISLAND_SIZE),
+                world.screen.dimensions(),
+                world.settings.ui_scale,
+            ),
+            moving_unit: None,
+            selected_unit: None,
+            placement_preview: None,
             current_ticks: 0,
             placed_units: 0,
             spawned_mobs: 0,
-            drop_rate,
+            round,
+            drop_tables,
+            seed,
+            rolls: RoundRolls::new(seed, round),
+            show_drop_odds: false,
+            hud: Hud::new(world.font),
+            effects: Effects::new(world.font),
+            unit_panel: UnitPanel::new(world.font),
+            recipe_book,
+            show_recipe_book: false,
+            hovered_recipe: None,
             current_user_action: None,
             state: LevelState::PickUnit,
-            sprite_layer: SpriteLayer::new(tilemap),
+            render_queue: RenderQueue::new(SpriteLayer::new(tilemap)),
+        }
+    }
+
+    /// Resumes a saved game at the start of the build phase it was saved in.
+    pub fn from_save(ctx: &mut ggez::Context, world: &mut World, save: &SaveGame) -> Self {
+        let mut scene = LevelScene::new(ctx, world, save.seed);
+
+        scene.start_round(save.round);
+
+        if let Some(bracket) = scene.drop_tables.borrow().0.bracket(save.round) {
+            scene.rolls = save.replay_rolls(bracket);
+        }
+
+        scene.placed_units = save.placed_units;
+
+        if scene.placed_units >= UNITS_PER_ROUND {
+            scene.state = LevelState::CombineUnit;
+        }
+
+        let units = &world.units;
+        let board = world.boards.get_mut(0).unwrap();
+
+        board.gold = save.gold;
+        board.lives = save.lives;
+        board.tiles = save
+            .units
+            .iter()
+            .map(|saved| {
+                let mut unit = units.create(saved.unit_type, saved.rank, saved.tile_position());
+                unit.target_priority = saved.target_priority;
+                unit
+            })
+            .collect();
+
+        scene.refresh_paths(&world.boards[0]);
+
+        scene
+    }
+
+    fn save(&self, ctx: &mut ggez::Context, world: &mut World) {
+        let board = &world.boards[0];
+
+        let save = SaveGame {
+            seed: self.seed,
+            round: self.round,
+            round_rolls: self.rolls.count(),
+            placed_units: self.placed_units,
+            gold: board.gold,
+            lives: board.lives,
+            units: SaveGame::saved_units(board),
+        };
+
+        match save.save(ctx) {
+            Ok(()) => world.game_saved = true,
+            Err(e) => warn!("Could not save the game: {}", e),
+        }
+    }
+
+    fn is_build_phase(&self) -> bool {
+        self.state == LevelState::PickUnit || self.state == LevelState::CombineUnit
+    }
+
+    /// Mob types are cycled through in order, one type per round.
+    fn round_mob_id(world: &World, round: u32) -> Option<String> {
+        let ids = world.mobs.ids();
+
+        if ids.is_empty() {
+            None
+        } else {
+            Some(ids[(round as usize - 1) % ids.len()].to_owned())
         }
     }
+
+    fn round_boss_id(world: &World, round: u32) -> Option<String> {
+        let ids = world.mobs.boss_ids();
+
+        if ids.is_empty() || round % BOSS_ROUND_INTERVAL != 0 {
+            None
+        } else {
+            Some(ids[(round / BOSS_ROUND_INTERVAL - 1) as usize % ids.len()].to_owned())
+        }
+    }
+
+    fn draw_boss_health(&self, world: &World, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let boss = world
+            .boards
+            .iter()
+            .flat_map(|board| board.mobs.iter())
+            .find(|mob| mob.boss);
+
+        if let Some(boss) = boss {
+            let screen = graphics::screen_coordinates(ctx);
+            let health_percentage = (boss.current_health as f32 / boss.max_health as f32).max(0.0);
+            let bar = graphics::Rect::new((screen.w - 400.0) / 2.0, 32.0, 400.0, 12.0);
+
+            let full_bar = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                bar,
+                graphics::Color::new(1.0, 0.0, 0.0, 1.0),
+            )?;
+
+            let current_bar = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(bar.x, bar.y, bar.w * health_percentage, bar.h),
+                if boss.is_invulnerable() {
+                    graphics::Color::new(1.0, 1.0, 0.0, 1.0)
+                } else {
+                    graphics::Color::new(0.0, 1.0, 0.0, 1.0)
+                },
+            )?;
+
+            let mut name_display = graphics::Text::new((boss.name.as_str(), world.font, 16.0));
+            name_display.set_bounds(na::Point2::new(bar.w, 20.0), graphics::Align::Center);
+
+            graphics::draw(ctx, &full_bar, graphics::DrawParam::default())?;
+            graphics::draw(ctx, &current_bar, graphics::DrawParam::default())?;
+            graphics::draw(
+                ctx,
+                &name_display,
+                (na::Point2::new(bar.x, bar.y - 18.0), graphics::WHITE),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn sell_unit(&mut self, world: &mut World, position: &na::Point2<i32>) {
+        if world.boards[0].sell(position).is_ok() {
+            self.refresh_paths(&world.boards[0]);
+        }
+    }
+
+    fn merge_unit(&mut self, world: &mut World, position: &na::Point2<i32>) {
+        if world.boards[0].tiles.merge(position).is_ok() {
+            self.refresh_paths(&world.boards[0]);
+        }
+    }
+
+    fn cycle_targeting(world: &mut World, position: &na::Point2<i32>) {
+        if let Some(unit) = world.boards[0].at_position_mut(position) {
+            unit.target_priority = unit.target_priority.next();
+        }
+    }
+
+    fn draw_unit_panel(&self, world: &World, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let board = &world.boards[0];
+
+        if let Some(unit) = self
+            .selected_unit
+            .and_then(|position| board.at_position(&position))
+        {
+            self.unit_panel.draw(
+                ctx,
+                world.screen.dimensions(),
+                unit,
+                &board.tiles.combinations_with(unit),
+                DESIRED_FPS as f32,
+                self.is_build_phase(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn hud_status(&self, world: &World) -> HudStatus {
+        let board = &world.boards[0];
+        let round_in_progress = self.state == LevelState::RoundStart;
+        let spawns_left = SPAWNS_PER_ROUND - self.spawned_mobs;
+
+        let next_spawn = if round_in_progress && spawns_left > 0 {
+            let ticks = SPAWN_INTERVAL - self.current_ticks % SPAWN_INTERVAL;
+            Some(ticks as f32 / world.updates_per_second() as f32)
+        } else {
+            None
+        };
+
+        let remaining_mobs = if round_in_progress {
+            let mut per_board = if LevelScene::round_mob_id(world, self.round).is_some() {
+                spawns_left
+            } else {
+                0
+            };
+
+            if self.spawned_mobs == 0 && LevelScene::round_boss_id(world, self.round).is_some() {
+                per_board += 1;
+            }
+
+            board.mobs.len() as u32 + per_board
+        } else {
+            0
+        };
+
+        HudStatus {
+            phase: self.state.label(),
+            round: self.round,
+            units_to_place: if self.state == LevelState::PickUnit {
+                UNITS_PER_ROUND - self.placed_units
+            } else {
+                0
+            },
+            gold: board.gold,
+            lives: board.lives,
+            path_length: self.path_overlay.length() as u32,
+            next_spawn,
+            remaining_mobs,
+        }
+    }
+
+    fn start_round(&mut self, round: u32) {
+        self.round = round;
+        self.placed_units = 0;
+        self.rolls = RoundRolls::new(self.seed, round);
+        self.placement_preview = None;
+        self.spawned_mobs = 0;
+        self.current_ticks = 0;
+        self.state = LevelState::PickUnit;
+    }
+
+    /// The unit `roll_unit` would give next, without rolling it.
+    fn peek_unit(&self) -> Option<(UnitType, Rank)> {
+        let drop_tables = self.drop_tables.borrow();
+        let bracket = drop_tables.0.bracket(self.round)?;
+
+        self.rolls.peek(bracket)
+    }
+
+    fn roll_unit(&mut self) -> Option<(UnitType, Rank)> {
+        let drop_tables = self.drop_tables.borrow();
+        let bracket = drop_tables.0.bracket(self.round)?;
+
+        self.rolls.roll(bracket)
+    }
+
+    fn draw_drop_odds(&self, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let drop_tables = self.drop_tables.borrow();
+
+        if let Some(bracket) = drop_tables.0.bracket(self.round) {
+            let mut lines = vec![format!(
+                "Round {} (odds from round {}), seed {}",
+                self.round, bracket.from_round, self.seed
+            )];
+
+            lines.extend(
+                bracket
+                    .unit_type_odds()
+                    .iter()
+                    .map(|(unit_type, odds)| format!("{:?}: {:.1}%", unit_type, odds * 100.0)),
+            );
+
+            lines.extend(
+                bracket
+                    .rank_odds()
+                    .iter()
+                    .map(|(rank, odds)| format!("Rank {}: {:.1}%", rank, odds * 100.0)),
+            );
+
+            let mut odds_display = graphics::Text::new(lines.join("\n"));
+            odds_display.set_bounds(na::Point2::new(300.0, 1000.0), graphics::Align::Left);
+
+            graphics::draw(
+                ctx,
+                &odds_display,
+                (na::Point2::new(0.0, 20.0), graphics::WHITE),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// The board tile under a point on screen, if there is one.
+    fn tile_at(&self, screen_point: &na::Point2<f32>) -> Option<na::Point2<i32>> {
+        let tile = self.camera.transform().screen_to_tile(screen_point);
+
+        if tile.x < 0 || tile.y < 0 || tile.x >= BOARD_WIDTH as i32 || tile.y >= BOARD_HEIGHT as i32
+        {
+            None
+        } else {
+            Some(tile)
+        }
+    }
+
+    fn update_camera(&mut self, world: &World, dt: f32) {
+        let viewport = world.screen.dimensions();
+
+        self.camera.set_viewport(viewport, world.settings.ui_scale);
+
+        let mut direction = na::Vector2::new(
+            world.input.get_axis_raw(input::Axis::Horz),
+            -world.input.get_axis_raw(input::Axis::Vert),
+        );
+
+        if let Some(mouse) = self.mouse_position {
+            for axis in 0..2 {
+                if mouse[axis] < EDGE_SCROLL_MARGIN {
+                    direction[axis] -= 1.0;
+                } else if mouse[axis] > viewport[axis] - EDGE_SCROLL_MARGIN {
+                    direction[axis] += 1.0;
+                }
+            }
+        }
+
+        self.camera.pan(direction * PAN_SPEED * dt);
+
+        self.hovered_tile = self.mouse_position.and_then(|mouse| self.tile_at(&mouse));
+    }
+
+    fn refresh_paths(&mut self, board: &Board) {
+        if let Some(lanes) = board.calculate_lane_paths() {
+            self.paths = lanes.concat();
+            self.path_overlay.set_lanes(lanes);
+            self.flow_fields = board.calculate_flow_fields();
+        }
+
+        self.placement_preview = None;
+    }
+
+    /// Previews the next unit on the hovered tile while units are being placed.
+    /// Only worked out again once the hovered tile or the board changes.
+    fn update_placement_preview(&mut self, world: &mut World) {
+        let placing = self.state == LevelState::PickUnit && self.placed_units < UNITS_PER_ROUND;
+        let board = &mut world.boards[0];
+
+        // Clicking a unit selects it rather than placing another one.
+        let tile = match self
+            .hovered_tile
+            .filter(|tile| placing && board.at_position(tile).is_none())
+        {
+            Some(tile) => tile,
+            None => {
+                self.placement_preview = None;
+                return;
+            }
+        };
+
+        if self.placement_preview.as_ref().map(|preview| preview.tile) == Some(tile) {
+            return;
+        }
+
+        let units = &world.units;
+
+        self.placement_preview = Some(PlacementPreview {
+            tile,
+            unit: self
+                .peek_unit()
+                .map(|(unit_type, rank)| units.create(unit_type, rank, tile)),
+            paths: board.preview_placement(&tile),
+        });
+    }
+
+    fn draw_placement_preview(
+        &mut self,
+        ctx: &mut ggez::Context,
+        transform: &Transform,
+    ) -> ggez::GameResult<()> {
+        let preview = match &self.placement_preview {
+            Some(preview) => preview,
+            None => return Ok(()),
+        };
+
+        if let Ok(paths) = &preview.paths {
+            let mut points: Vec<na::Point2<f32>> =
+                paths.iter().map(Transform::tile_center).collect();
+            points.dedup();
+
+            if points.len() >= 2 {
+                let path = graphics::Mesh::new_line(
+                    ctx,
+                    &points,
+                    2.0,
+                    graphics::Color::new(1.0, 1.0, 1.0, 0.5),
+                )?;
+
+                self.render_queue
+                    .draw(RenderLayer::Decals, 0.0, path, transform.draw_param());
+            }
+        }
+
+        if let Some(unit) = &preview.unit {
+            let corner = Transform::tile_to_world(&preview.tile);
+
+            self.render_queue.sprite(
+                RenderLayer::Units,
+                Tile {
+                    sprite_layer: 0,
+                    sprite_id: unit.current_sprite_id(),
+                },
+                corner,
+                graphics::Color::new(1.0, 1.0, 1.0, 0.5),
+            );
+
+            if unit.range > 0.0 {
+                let circle = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.0),
+                    Transform::tile_center(&preview.tile),
+                    unit.range,
+                    1.0,
+                    graphics::Color::new(1.0, 1.0, 1.0, 0.5),
+                )?;
+
+                self.render_queue
+                    .draw(RenderLayer::Decals, 0.0, circle, transform.draw_param());
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl scene::Scene<World, input::Event> for LevelScene {
-    fn update(&mut self, gameworld: &mut World, _ctx: &mut ggez::Context) -> scenes::Switch {
-        let dt = 1.0 / 60.0;
+    fn update(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> scenes::Switch {
+        let dt = TICK_LENGTH;
+
+        match gameworld.pause_action.take() {
+            Some(PauseAction::Restart) => {
+                *self = LevelScene::new(ctx, gameworld, self.seed);
+            }
+            Some(PauseAction::Save) => {
+                self.save(ctx, gameworld);
+                self.paused = true;
+            }
+            Some(PauseAction::QuitToMenu) => self.done = true,
+            None => {}
+        }
+
+        if self.done {
+            gameworld.fast_forward = false;
+            return scene::SceneSwitch::Pop;
+        }
+
+        if self.paused {
+            self.paused = false;
+            let can_save = self.is_build_phase();
+            return scene::SceneSwitch::Push(Box::new(PauseScene::new(ctx, gameworld, can_save)));
+        }
+
+        self.update_camera(gameworld, dt);
 
         if self.state == LevelState::RoundStart {
             self.current_ticks += 1;
 
-            if self.spawned_mobs < 10 {
-                if self.current_ticks % 60 == 0 {
+            if self.spawned_mobs < SPAWNS_PER_ROUND {
+                if self.current_ticks % SPAWN_INTERVAL == 0 {
+                    let mut mob_ids: Vec<String> = LevelScene::round_mob_id(gameworld, self.round)
+                        .into_iter()
+                        .collect();
+
+                    if self.spawned_mobs == 0 {
+                        mob_ids.extend(LevelScene::round_boss_id(gameworld, self.round));
+                    }
+
+                    let mobs = &gameworld.mobs;
+                    let definitions: Vec<&mob::MobDefinition> =
+                        mob_ids.iter().filter_map(|id| mobs.get(id)).collect();
+
                     for board in &mut gameworld.boards {
-                        let chicken: mob::MobEntity = (&self.chicken_definition.borrow().0).into();
-                        board.mobs.push(chicken);
+                        board.spawn_wave(&definitions);
                     }
 
                     self.spawned_mobs += 1;
@@ -120,278 +666,393 @@ impl scene::Scene<World, input::Event> for LevelScene {
             }
         }
 
-        if self.spawned_mobs == 10
-            && gameworld.boards.iter().all(|board| board.mobs.len() == 0)
-            && self.state == LevelState::RoundStart
+        if self.state == LevelState::RoundStart
+            && gameworld
+                .boards
+                .iter()
+                .all(|board| !board.wave_in_progress())
         {
             self.state = LevelState::RoundEnd;
+        } else if self.state == LevelState::RoundEnd {
+            self.start_round(self.round + 1);
         }
 
+        let first_event = gameworld.events.len();
+
         for board in &mut gameworld.boards {
             for mob in board.mobs.iter_mut() {
                 mob.update(dt);
 
                 if mob.status == mob::MobEntityStatus::FinishedPath {
-                    mob.path_index += 1;
-
-                    if (mob.path_index as usize) < self.paths.len() {
-                        let new_path = self.paths[mob.path_index as usize];
-
-                        mob.destination = na::Point2::new(new_path.x as f32, new_path.y as f32);
-                        mob.status = mob::MobEntityStatus::Walking;
+                    match (mob.movement, self.navigation_mode) {
+                        (mob::MovementClass::Flying, _) => mob.follow_path(&self.checkpoints),
+                        (mob::MovementClass::Ground, NavigationMode::AStar) => {
+                            mob.follow_path(&self.paths)
+                        }
+                        (mob::MovementClass::Ground, NavigationMode::FlowField) => {
+                            mob.follow_flow_fields(&self.flow_fields)
+                        }
                     }
                 }
             }
 
-            board.update();
+            board.remove_leaked_mobs();
+            board.update(dt);
+            board.update_animations(dt);
+            board.spawn_pending(&gameworld.mobs);
+
+            gameworld.events.append(&mut board.events);
+        }
+
+        for event in &gameworld.events[first_event..] {
+            self.effects.handle(event);
+            self.hud.handle(event);
         }
 
-        if let Some(action) = &self.current_user_action {
+        self.effects.update(dt);
+        self.hud.update(dt);
+
+        self.update_placement_preview(gameworld);
+
+        if let Some(action) = self.current_user_action.take() {
             match action {
                 UserAction::BuildUnit => {
-                    if self.placed_units < 5 && self.state == LevelState::PickUnit {
-                        if let Some(hovered_tile) = self.hovered_tile {
+                    let clicked_unit = self
+                        .hovered_tile
+                        .filter(|tile| gameworld.boards[0].at_position(tile).is_some());
+
+                    self.selected_unit = clicked_unit;
+
+                    if clicked_unit.is_none()
+                        && self.placed_units < UNITS_PER_ROUND
+                        && self.state == LevelState::PickUnit
+                    {
+                        // Only roll for legal tiles, so the unit placed is the one previewed.
+                        let legal_tile = match &self.placement_preview {
+                            Some(preview)
+                                if preview.paths.is_ok()
+                                    && Some(preview.tile) == self.hovered_tile =>
+                            {
+                                Some(preview.tile)
+                            }
+                            _ => None,
+                        };
+
+                        if let Some((hovered_tile, (unit_type, rank))) =
+                            legal_tile.and_then(|tile| self.roll_unit().map(|roll| (tile, roll)))
+                        {
                             let board = gameworld.boards.get_mut(0).unwrap();
-                            let rank = self.drop_rate.random().unwrap();
-                            let unit_type = vec![
-                                unit::UnitType::Cleric,
-                                unit::UnitType::Mage,
-                                unit::UnitType::Ranger,
-                                unit::UnitType::Scout,
-                                unit::UnitType::Warrior,
-                            ]
-                            .choose(&mut rand::thread_rng())
-                            .unwrap()
-                            .to_owned();
-
-                            board.tiles.push(unit::Unit {
-                                rank,
-                                unit_type,
-                                range: 36.0,
-                                tile_position: na::Point2::new(
-                                    hovered_tile.x as i32,
-                                    hovered_tile.y as i32,
-                                ),
-                                ..unit::Unit::default()
-                            });
-
-                            self.placed_units += 1;
-
-                            if self.placed_units == 5 {
-                                self.state = LevelState::CombineUnit;
-                                self.current_ticks = 0;
+
+                            let placed =
+                                board.place(gameworld.units.create(unit_type, rank, hovered_tile));
+
+                            if placed.is_ok() {
+                                self.refresh_paths(&gameworld.boards[0]);
+                                self.placed_units += 1;
+
+                                if self.placed_units == UNITS_PER_ROUND {
+                                    self.state = LevelState::CombineUnit;
+                                    self.current_ticks = 0;
+                                }
                             }
                         }
-                    } else if self.state == LevelState::CombineUnit {
-                        if let Some(hovered_tile) = self.hovered_tile {
-                            let board = gameworld.boards.get_mut(0).unwrap();
-                            let board_position = na::Point2::<i32>::new(
-                                hovered_tile.x as i32,
-                                hovered_tile.y as i32,
-                            );
+                    }
+                }
+                UserAction::SellUnit => {
+                    if let Some(board_position) =
+                        self.hovered_tile.filter(|_| self.is_build_phase())
+                    {
+                        self.sell_unit(gameworld, &board_position);
+                    }
+                }
+                UserAction::MoveUnit => {
+                    if let Some(board_position) =
+                        self.hovered_tile.filter(|_| self.is_build_phase())
+                    {
+                        let board = gameworld.boards.get_mut(0).unwrap();
 
-                            if let Some(_unit) = board.at_position(&board_position) {
-                                self.state = LevelState::RoundStart;
+                        match self.moving_unit.take() {
+                            Some(from) => {
+                                if board.relocate(&from, &board_position).is_ok() {
+                                    self.refresh_paths(&gameworld.boards[0]);
+
+                                    if self.selected_unit == Some(from) {
+                                        self.selected_unit = Some(board_position);
+                                    } else if self.selected_unit == Some(board_position) {
+                                        self.selected_unit = Some(from);
+                                    }
+                                }
+                            }
+                            None => {
+                                if board.at_position(&board_position).is_some() {
+                                    self.moving_unit = Some(board_position);
+                                }
+                            }
+                        }
+                    }
+                }
+                UserAction::MergeUnit => {
+                    if let Some(board_position) =
+                        self.hovered_tile.filter(|_| self.is_build_phase())
+                    {
+                        self.merge_unit(gameworld, &board_position);
+                    }
+                }
+                UserAction::Panel(button) => {
+                    if let Some(selected) = self.selected_unit {
+                        match button {
+                            PanelButton::Sell if self.is_build_phase() => {
+                                self.sell_unit(gameworld, &selected)
+                            }
+                            PanelButton::Combine if self.is_build_phase() => {
+                                self.merge_unit(gameworld, &selected)
+                            }
+                            PanelButton::Targeting => {
+                                LevelScene::cycle_targeting(gameworld, &selected)
                             }
+                            _ => {}
                         }
                     }
                 }
             }
         }
 
-        self.current_user_action = None;
+        // Sold or merged away units can't stay selected.
+        let board = &gameworld.boards[0];
+        self.selected_unit = self
+            .selected_unit
+            .filter(|position| board.at_position(position).is_some());
 
-        if self.done {
-            scene::SceneSwitch::Pop
-        } else {
-            scene::SceneSwitch::None
-        }
+        self.update_placement_preview(gameworld);
+
+        scene::SceneSwitch::None
     }
 
     fn draw(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let transform = self.camera.transform();
         let dt =
             (ggez::timer::remaining_update_time(ctx).as_millis() as f32 / 1000.0) / (1.0 / 60.0);
 
-        graphics::draw(
-            ctx,
-            &(self.bg.borrow().0),
+        self.render_queue.draw(
+            RenderLayer::Terrain,
+            0.0,
+            self.bg.borrow().0.clone(),
             graphics::DrawParam::default().scale(na::Vector2::new(4.0, 4.0)),
-        )?;
-
-        let island_dimension = graphics::Rect::new(0.0, 0.0, 656.0 * SCALE_X, 656.0 * SCALE_Y);
-        let calculated_dimensions = gameworld.screen.center_fit(&island_dimension);
+        );
 
-        graphics::draw(
-            ctx,
-            &(self.island.borrow().0),
+        self.render_queue.draw(
+            RenderLayer::Terrain,
+            0.0,
+            self.island.borrow().0.clone(),
             graphics::DrawParam::default()
-                .scale(na::Vector2::new(SCALE_X, SCALE_Y))
-                .dest(na::Point2::new(
-                    calculated_dimensions.x,
-                    calculated_dimensions.y,
-                )),
-        )?;
+                .dest(transform.world_to_screen(&na::Point2::new(-ISLAND_BORDER, -ISLAND_BORDER)))
+                .scale(na::Vector2::new(transform.scale, transform.scale)),
+        );
+
+        if self.show_paths {
+            self.path_overlay
+                .draw(ctx, &transform, &mut self.render_queue)?;
+        }
+
+        let half_tile = na::Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
 
         for board in &gameworld.boards {
             for unit in &board.tiles {
-                self.sprite_layer.add(
-                    &Tile {
+                let corner = Transform::tile_to_world(&unit.tile_position);
+
+                self.render_queue.sprite(
+                    RenderLayer::Units,
+                    Tile {
                         sprite_layer: 0,
-                        sprite_id: 5,
+                        sprite_id: unit.current_sprite_id(),
                     },
-                    (unit.tile_position.x as f32 * 16.0) + 8.0,
-                    (unit.tile_position.y as f32 * 16.0) + 8.0,
+                    corner,
+                    graphics::WHITE,
                 );
 
                 let circle = graphics::Mesh::new_circle(
                     ctx,
                     graphics::DrawMode::stroke(2.0),
-                    na::Point2::new(
-                        (unit.tile_position.x as f32 * 16.0) + (unit.range / 2.0) + 6.0,
-                        (unit.tile_position.y as f32 * 16.0) + (unit.range / 2.0) + 6.0,
-                    ),
+                    Transform::tile_center(&unit.tile_position),
                     unit.range,
                     1.0,
                     graphics::Color::new(1.0, 0.0, 0.0, 0.35),
                 )?;
 
-                if let Some(hover_coords) = self.hovered_tile {
-                    if hover_coords.x == unit.tile_position.x as u32
-                        && hover_coords.y == unit.tile_position.y as u32
-                    {
-                        let mut type_display = graphics::Text::new(format!(
-                            "{:?} - Rank {:?}",
-                            unit.unit_type, unit.rank
-                        ));
-                        type_display
-                            .set_bounds(na::Point2::new(200.0, 50.0), graphics::Align::Left);
-                        graphics::draw(
-                            ctx,
-                            &type_display,
-                            graphics::DrawParam::default().dest(na::Point2::new(
-                                ((unit.tile_position.x as f32 * 16.0) + 4.0) * SCALE_X
-                                    + calculated_dimensions.x,
-                                ((unit.tile_position.y as f32 * 16.0) - 4.0) * SCALE_Y
-                                    + calculated_dimensions.y,
-                            )),
-                        )?;
-                    }
+                self.render_queue
+                    .draw(RenderLayer::Decals, 0.0, circle, transform.draw_param());
+
+                if self.hovered_tile == Some(unit.tile_position) {
+                    let mut type_display = graphics::Text::new(format!(
+                        "{:?} - Rank {:?} - {:?}",
+                        unit.unit_type, unit.rank, unit.target_priority
+                    ));
+                    type_display.set_bounds(na::Point2::new(300.0, 50.0), graphics::Align::Left);
+
+                    self.render_queue.draw(
+                        RenderLayer::Ui,
+                        0.0,
+                        type_display,
+                        graphics::DrawParam::default().dest(
+                            transform.world_to_screen(&(corner - na::Vector2::new(0.0, TILE_SIZE))),
+                        ),
+                    );
                 }
+            }
 
-                graphics::draw(
-                    ctx,
-                    &circle,
-                    graphics::DrawParam::default()
-                        .dest(na::Point2::new(
-                            calculated_dimensions.x,
-                            calculated_dimensions.y,
-                        ))
-                        .scale(na::Vector2::new(SCALE_X, SCALE_Y)),
-                )?;
+            for mob in board.dying.iter() {
+                self.render_queue.sprite(
+                    RenderLayer::Mobs,
+                    Tile {
+                        sprite_layer: mob.spritesheet_id as i32,
+                        sprite_id: mob.sprite_id(),
+                    },
+                    mob.position - half_tile,
+                    graphics::WHITE,
+                );
             }
 
             for mob in board.mobs.iter() {
-                self.sprite_layer.add(
-                    &Tile {
-                        sprite_layer: 0,
-                        sprite_id: 350,
+                let position = mob.last_position + (mob.position - mob.last_position) * dt;
+                let corner = position - half_tile;
+
+                let flash = 1.0 - self.effects.flash(mob.id) * 0.7;
+
+                self.render_queue.sprite(
+                    RenderLayer::Mobs,
+                    Tile {
+                        sprite_layer: mob.spritesheet_id as i32,
+                        sprite_id: mob.sprite_id(),
                     },
-                    mob.last_position.x + 8.0 + (mob.position.x - mob.last_position.x) * dt,
-                    mob.last_position.y + 8.0 + (mob.position.y - mob.last_position.y) * dt,
+                    corner,
+                    graphics::Color::new(1.0, flash, flash, 1.0),
                 );
 
                 if mob.show_health_bar() {
                     let health_percentage = mob.current_health as f32 / mob.max_health as f32;
                     let width = 20.0 * health_percentage;
+                    // Centered just above the sprite.
+                    let bar = corner + na::Vector2::new(-2.0, -4.0);
 
                     let current_bar = graphics::Mesh::new_rectangle(
                         ctx,
                         graphics::DrawMode::fill(),
-                        graphics::Rect::new(
-                            mob.position.x + 12.0,
-                            mob.position.y + 8.0,
-                            width,
-                            2.0,
-                        ),
+                        graphics::Rect::new(bar.x, bar.y, width, 2.0),
                         graphics::Color::new(0.0, 1.0, 0.0, 1.0),
                     )?;
 
                     let full_bar = graphics::Mesh::new_rectangle(
                         ctx,
                         graphics::DrawMode::fill(),
-                        graphics::Rect::new(mob.position.x + 12.0, mob.position.y + 8.0, 20.0, 2.0),
+                        graphics::Rect::new(bar.x, bar.y, 20.0, 2.0),
                         graphics::Color::new(1.0, 0.0, 0.0, 1.0),
                     )?;
 
-                    graphics::draw(
-                        ctx,
-                        &full_bar,
-                        graphics::DrawParam::default()
-                            .dest(na::Point2::new(
-                                calculated_dimensions.x,
-                                calculated_dimensions.y,
-                            ))
-                            .scale(na::Vector2::new(SCALE_X, SCALE_Y)),
-                    )?;
-
-                    graphics::draw(
-                        ctx,
-                        &current_bar,
-                        graphics::DrawParam::default()
-                            .dest(na::Point2::new(
-                                calculated_dimensions.x,
-                                calculated_dimensions.y,
-                            ))
-                            .scale(na::Vector2::new(SCALE_X, SCALE_Y)),
-                    )?;
+                    // Sorted along with the mob's sprite, so other mobs walk in front of the bar too.
+                    self.render_queue.draw(
+                        RenderLayer::Mobs,
+                        corner.y,
+                        full_bar,
+                        transform.draw_param(),
+                    );
+                    self.render_queue.draw(
+                        RenderLayer::Mobs,
+                        corner.y,
+                        current_bar,
+                        transform.draw_param(),
+                    );
                 }
             }
         }
 
-        // Given a 15x15 board and the screen dimensions,
-        // Rect = screen.get_center_for_rect(Rect{ x: 0.0, y: 0.0, width: BOARD_WIDTH, height: BOARD_HEIGHT})
-        // each tile is 16px * 40 = 640px unscaled width & height
+        self.draw_placement_preview(ctx, &transform)?;
 
-        let board_dimensions = graphics::Rect::new(0.0, 0.0, 640.0 * SCALE_X, 640.0 * SCALE_Y);
-        let calculated_dimensions = gameworld.screen.center_fit(&board_dimensions);
+        if let Some(hovered_tile) = self.hovered_tile {
+            let corner = Transform::tile_to_world(&hovered_tile);
+
+            // While placing, the tile shows whether the unit can go there.
+            let color = match &self.placement_preview {
+                Some(PlacementPreview { paths: Ok(_), .. }) => {
+                    graphics::Color::new(0.0, 1.0, 0.0, 0.4)
+                }
+                Some(PlacementPreview { paths: Err(_), .. }) => {
+                    graphics::Color::new(1.0, 0.0, 0.0, 0.4)
+                }
+                None => graphics::Color::new(0.0, 1.0, 0.0, 1.0),
+            };
 
-        graphics::draw(
-            ctx,
-            &self.sprite_layer.batch,
-            graphics::DrawParam::default()
-                .dest(na::Point2::new(
-                    calculated_dimensions.x,
-                    calculated_dimensions.y,
-                ))
-                .scale(na::Vector2::new(SCALE_X, SCALE_Y)),
-        )?;
-
-        if let Some(hover_coords) = self.hovered_tile {
             let tile_hover = graphics::Mesh::new_rectangle(
                 ctx,
                 graphics::DrawMode::fill(),
-                graphics::Rect::new(
-                    (hover_coords.x as f32 * 16.0) + 8.0,
-                    (hover_coords.y as f32 * 16.0) + 8.0,
-                    16.0,
-                    16.0,
-                ),
-                graphics::Color::new(0.0, 1.0, 0.0, 1.0),
+                graphics::Rect::new(corner.x, corner.y, TILE_SIZE, TILE_SIZE),
+                color,
             )?;
 
-            graphics::draw(
+            self.render_queue.draw(
+                RenderLayer::Effects,
+                0.0,
+                tile_hover,
+                transform.draw_param(),
+            );
+        }
+
+        if let Some(selected_unit) = self.selected_unit {
+            let corner = Transform::tile_to_world(&selected_unit);
+
+            let selection = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(1.0),
+                graphics::Rect::new(corner.x, corner.y, TILE_SIZE, TILE_SIZE),
+                graphics::Color::new(1.0, 1.0, 0.0, 1.0),
+            )?;
+
+            self.render_queue
+                .draw(RenderLayer::Effects, 0.0, selection, transform.draw_param());
+        }
+
+        if let Some(recipe) = self.hovered_recipe {
+            for position in gameworld.boards[0].tiles.ingredients(recipe) {
+                let corner = Transform::tile_to_world(&position);
+
+                let ingredient = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::stroke(1.0),
+                    graphics::Rect::new(corner.x, corner.y, TILE_SIZE, TILE_SIZE),
+                    graphics::Color::new(0.0, 1.0, 1.0, 1.0),
+                )?;
+
+                self.render_queue.draw(
+                    RenderLayer::Effects,
+                    0.0,
+                    ingredient,
+                    transform.draw_param(),
+                );
+            }
+        }
+
+        self.effects.draw(ctx, &transform, &mut self.render_queue)?;
+
+        self.render_queue.flush(ctx, transform.draw_param())?;
+
+        self.draw_boss_health(gameworld, ctx)?;
+        self.hud.draw(ctx, &self.hud_status(gameworld))?;
+        self.draw_unit_panel(gameworld, ctx)?;
+
+        if self.show_recipe_book {
+            let units = &gameworld.boards[0].tiles;
+
+            self.recipe_book.draw(
                 ctx,
-                &tile_hover,
-                graphics::DrawParam::default()
-                    .dest(na::Point2::new(
-                        calculated_dimensions.x,
-                        calculated_dimensions.y,
-                    ))
-                    .scale(na::Vector2::new(1.5, 1.5)),
+                gameworld.screen.dimensions(),
+                units,
+                &units.combinations(),
+                self.hovered_recipe,
             )?;
         }
 
-        self.sprite_layer.clear();
+        if self.show_drop_odds {
+            self.draw_drop_odds(ctx)?;
+        }
 
         Ok(())
     }
@@ -403,28 +1064,119 @@ impl scene::Scene<World, input::Event> for LevelScene {
     fn input(&mut self, gameworld: &mut World, ev: input::Event, _started: bool) {
         if let input::InputEvent::InputEffect(_) = ev {
             if gameworld.input.get_button_pressed(input::Button::Menu) {
-                self.done = true;
+                self.paused = true;
             }
 
             if gameworld.input.get_button_pressed(input::Button::Select) {
-                self.current_user_action = Some(UserAction::BuildUnit);
+                let screen = gameworld.screen.dimensions();
+
+                // Clicks on the unit panel are for the panel, not the board underneath.
+                self.current_user_action = match self.mouse_position {
+                    Some(mouse)
+                        if self.selected_unit.is_some() && UnitPanel::contains(screen, &mouse) =>
+                    {
+                        UnitPanel::button_at(screen, &mouse).map(UserAction::Panel)
+                    }
+                    Some(mouse)
+                        if self.show_recipe_book && RecipeBook::contains(screen, &mouse) =>
+                    {
+                        None
+                    }
+                    _ => Some(UserAction::BuildUnit),
+                };
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::Sell) {
+                self.current_user_action = Some(UserAction::SellUnit);
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::Move) {
+                self.current_user_action = Some(UserAction::MoveUnit);
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::Merge) {
+                self.current_user_action = Some(UserAction::MergeUnit);
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::Cancel) {
+                self.current_user_action = None;
+                self.moving_unit = None;
+                self.selected_unit = None;
+            }
+
+            if gameworld
+                .input
+                .get_button_pressed(input::Button::StartRound)
+                && self.state == LevelState::CombineUnit
+            {
+                self.state = LevelState::RoundStart;
+
+                for board in gameworld.boards.iter_mut() {
+                    board.start_wave(self.round, SPAWNS_PER_ROUND);
+                }
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::SpeedUp) {
+                gameworld.fast_forward = !gameworld.fast_forward;
+            }
+
+            if gameworld
+                .input
+                .get_button_pressed(input::Button::CycleTargeting)
+            {
+                if let Some(hovered_tile) = self.hovered_tile {
+                    LevelScene::cycle_targeting(gameworld, &hovered_tile);
+                }
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::Debug) {
+                self.show_drop_odds = !self.show_drop_odds;
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::Paths) {
+                self.show_paths = !self.show_paths;
+            }
+
+            if gameworld.input.get_button_pressed(input::Button::Recipes) {
+                self.show_recipe_book = !self.show_recipe_book;
+                self.hovered_recipe = None;
+            }
+
+            // Mobs keep their progress in a form specific to the mode, so only switch between rounds.
+            if gameworld
+                .input
+                .get_button_pressed(input::Button::Navigation)
+                && self.is_build_phase()
+            {
+                self.navigation_mode = match self.navigation_mode {
+                    NavigationMode::AStar => NavigationMode::FlowField,
+                    NavigationMode::FlowField => NavigationMode::AStar,
+                };
             }
         }
 
+        if let input::InputEvent::MouseWheel(amount) = ev {
+            let anchor = self
+                .mouse_position
+                .unwrap_or_else(|| na::Point2::from(gameworld.screen.dimensions() / 2.0));
+
+            self.camera.zoom_at(&anchor, ZOOM_STEP.powf(amount));
+        }
+
         if let input::InputEvent::MouseEffect(effect) = ev {
-            // @TODO(vy): Clean this up, but this calculates the mouse position relative to the
-            // sprite tileboard created at scale.
-            let (x, y) = (effect.x, effect.y);
-            let island_dimension = graphics::Rect::new(0.0, 0.0, 656.0 * SCALE_X, 656.0 * SCALE_Y);
-            let calculated_dimensions = gameworld.screen.center_fit(&island_dimension);
-
-            let offset_x = ((x - calculated_dimensions.x) - 24.0) / SCALE_X;
-            let offset_y = ((y - calculated_dimensions.y) - 24.0) / SCALE_Y;
-
-            self.hovered_tile = Some(na::Point2::new(
-                (na::clamp(offset_x, 0.0, 656.0 * SCALE_X) / 16.0) as u32,
-                (na::clamp(offset_y, 0.0, 656.0 * SCALE_Y) / 16.0) as u32,
-            ));
+            let mouse = na::Point2::new(effect.x, effect.y);
+
+            if gameworld.input.get_button_down(input::Button::Pan) {
+                self.camera.pan(-na::Vector2::new(effect.dx, effect.dy));
+            }
+
+            self.mouse_position = Some(mouse);
+            self.hovered_tile = self.tile_at(&mouse);
+            self.hovered_recipe = if self.show_recipe_book {
+                RecipeBook::recipe_at(gameworld.screen.dimensions(), &mouse)
+            } else {
+                None
+            };
         }
     }
 }
@@ -1,33 +1,122 @@
 use ggez;
+use ggez::nalgebra as na;
+use ggez_goodies::input::InputEffect;
 use ggez_goodies::scene;
+use log::*;
 
+use crate::game::save::SaveGame;
 use crate::input;
 use crate::scenes;
+use crate::scenes::level::LevelScene;
+use crate::scenes::menu_list::MenuList;
+use crate::scenes::settings::SettingsScene;
 use crate::world::World;
 
+const MAX_SEED_DIGITS: usize = 19;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuItem {
+    NewGame,
+    Continue,
+    SeededGame,
+    Settings,
+    Quit,
+}
+
+const MENU_ITEMS: [MenuItem; 5] = [
+    MenuItem::NewGame,
+    MenuItem::Continue,
+    MenuItem::SeededGame,
+    MenuItem::Settings,
+    MenuItem::Quit,
+];
+
+impl MenuItem {
+    fn label(self) -> &'static str {
+        match self {
+            MenuItem::NewGame => "New Game",
+            MenuItem::Continue => "Continue",
+            MenuItem::SeededGame => "Seeded Game",
+            MenuItem::Settings => "Settings",
+            MenuItem::Quit => "Quit",
+        }
+    }
+}
+
 pub struct MenuScene {
-    done: bool,
+    list: MenuList<MenuItem>,
+    activated: Option<MenuItem>,
+    /// Digits typed so far, while the seed of a seeded game is being entered.
+    seed_entry: Option<String>,
 }
 
 impl MenuScene {
-    pub fn new(_ctx: &mut ggez::Context, _world: &mut World) -> Self {
-        let done = false;
+    pub fn new(ctx: &mut ggez::Context, world: &mut World) -> Self {
+        let mut list = MenuList::new(world.font, &MENU_ITEMS);
+        list.set_enabled(MenuItem::Continue, SaveGame::exists(ctx));
 
-        MenuScene { done }
+        MenuScene {
+            list,
+            activated: None,
+            seed_entry: None,
+        }
     }
 }
 
 impl scene::Scene<World, input::Event> for MenuScene {
-    fn update(&mut self, _gameworld: &mut World, _ctx: &mut ggez::Context) -> scenes::Switch {
-        if self.done {
-            scene::SceneSwitch::Pop
-        } else {
-            scene::SceneSwitch::None
+    fn update(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> scenes::Switch {
+        // Saves only appear when the level writes one, so the file is not looked for every frame.
+        if gameworld.game_saved {
+            gameworld.game_saved = false;
+            self.list.set_enabled(MenuItem::Continue, true);
+        }
+
+        match self.activated.take() {
+            Some(MenuItem::NewGame) => {
+                scene::SceneSwitch::Push(Box::new(LevelScene::new(ctx, gameworld, rand::random())))
+            }
+            Some(MenuItem::SeededGame) => {
+                match self.seed_entry.as_ref().map(|seed| seed.parse::<u64>()) {
+                    Some(Ok(seed)) => {
+                        self.seed_entry = None;
+                        scene::SceneSwitch::Push(Box::new(LevelScene::new(ctx, gameworld, seed)))
+                    }
+                    Some(Err(_)) => scene::SceneSwitch::None,
+                    None => {
+                        self.seed_entry = Some(String::new());
+                        scene::SceneSwitch::None
+                    }
+                }
+            }
+            Some(MenuItem::Continue) => match SaveGame::load(ctx) {
+                Ok(save) => {
+                    scene::SceneSwitch::Push(Box::new(LevelScene::from_save(ctx, gameworld, &save)))
+                }
+                Err(e) => {
+                    warn!("Could not load the saved game: {}", e);
+                    self.list.set_enabled(MenuItem::Continue, false);
+                    scene::SceneSwitch::None
+                }
+            },
+            Some(MenuItem::Settings) => {
+                scene::SceneSwitch::Push(Box::new(SettingsScene::new(ctx, gameworld)))
+            }
+            Some(MenuItem::Quit) => {
+                ggez::event::quit(ctx);
+                scene::SceneSwitch::None
+            }
+            _ => scene::SceneSwitch::None,
         }
     }
 
-    fn draw(&mut self, _gameworld: &mut World, _ctx: &mut ggez::Context) -> ggez::GameResult<()> {
-        Ok(())
+    fn draw(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let seed_entry = &self.seed_entry;
+
+        self.list
+            .draw(gameworld, ctx, "Towers", |item| match (item, seed_entry) {
+                (MenuItem::SeededGame, Some(seed)) => format!("Seed: {}_", seed),
+                _ => item.label().to_owned(),
+            })
     }
 
     fn name(&self) -> &str {
@@ -35,10 +124,41 @@ impl scene::Scene<World, input::Event> for MenuScene {
     }
 
     fn input(&mut self, gameworld: &mut World, ev: input::Event, _started: bool) {
-        if let input::InputEvent::InputEffect(_) = ev {
-            if gameworld.input.get_button_pressed(input::Button::Menu) {
-                self.done = true;
+        match ev {
+            input::InputEvent::InputEffect(InputEffect::Axis(input::Axis::Vert, up)) => {
+                if gameworld.input.get_axis_raw(input::Axis::Vert) != 0.0 {
+                    self.seed_entry = None;
+                    self.list.move_selection(up);
+                }
+            }
+            input::InputEvent::InputEffect(_) => {
+                if gameworld.input.get_button_pressed(input::Button::Select) {
+                    let item = self.list.selected();
+
+                    if self.list.is_enabled(item) {
+                        self.activated = Some(item);
+                    }
+                }
+
+                if gameworld.input.get_button_pressed(input::Button::Quit) {
+                    self.seed_entry = None;
+                }
+            }
+            input::InputEvent::MouseEffect(effect) => {
+                let point = na::Point2::new(effect.x, effect.y);
+
+                if self.list.hover(gameworld, point) {
+                    self.seed_entry = None;
+                }
+            }
+            input::InputEvent::Text(character) => {
+                if let Some(seed) = self.seed_entry.as_mut() {
+                    if character.is_ascii_digit() && seed.len() < MAX_SEED_DIGITS {
+                        seed.push(character);
+                    }
+                }
             }
+            _ => {}
         }
     }
 }
@@ -0,0 +1,154 @@
+use ggez;
+use ggez::graphics;
+use ggez::nalgebra as na;
+
+use crate::world::World;
+
+const ITEM_WIDTH: f32 = 400.0;
+const ITEM_HEIGHT: f32 = 48.0;
+const COMPACT_ITEM_HEIGHT: f32 = 32.0;
+
+/// A vertical list of menu entries centered on the screen, with one of them selected.
+/// Shared by the menus so they navigate and look the same.
+pub struct MenuList<T> {
+    font: graphics::Font,
+    items: Vec<(T, bool)>,
+    selected: usize,
+    item_height: f32,
+}
+
+impl<T: Copy + PartialEq> MenuList<T> {
+    pub fn new(font: graphics::Font, items: &[T]) -> Self {
+        MenuList {
+            font,
+            items: items.iter().map(|item| (*item, true)).collect(),
+            selected: 0,
+            item_height: ITEM_HEIGHT,
+        }
+    }
+
+    /// Packs the items closer together, for lists too long to fit otherwise.
+    pub fn compact(mut self) -> Self {
+        self.item_height = COMPACT_ITEM_HEIGHT;
+        self
+    }
+
+    pub fn selected(&self) -> T {
+        self.items[self.selected].0
+    }
+
+    pub fn is_enabled(&self, item: T) -> bool {
+        self.items
+            .iter()
+            .any(|(other, enabled)| *other == item && *enabled)
+    }
+
+    pub fn set_enabled(&mut self, item: T, enabled: bool) {
+        for entry in self.items.iter_mut().filter(|(other, _)| *other == item) {
+            entry.1 = enabled;
+        }
+
+        if !self.items[self.selected].1 {
+            self.move_selection(false);
+        }
+    }
+
+    pub fn item_rect(&self, world: &World, index: usize) -> graphics::Rect {
+        let menu = world.screen.center_fit(&graphics::Rect::new(
+            0.0,
+            0.0,
+            ITEM_WIDTH,
+            self.item_height * self.items.len() as f32,
+        ));
+
+        graphics::Rect::new(
+            menu.x,
+            menu.y + self.item_height * index as f32,
+            ITEM_WIDTH,
+            self.item_height,
+        )
+    }
+
+    /// Moves the selection up or down, skipping over disabled items.
+    pub fn move_selection(&mut self, up: bool) {
+        let count = self.items.len();
+        let mut index = self.selected;
+
+        for _ in 0..count {
+            index = if up {
+                (index + count - 1) % count
+            } else {
+                (index + 1) % count
+            };
+
+            if self.items[index].1 {
+                self.selected = index;
+                return;
+            }
+        }
+    }
+
+    /// Selects the enabled item under the mouse, returning whether the selection changed.
+    pub fn hover(&mut self, world: &World, point: na::Point2<f32>) -> bool {
+        let hovered = (0..self.items.len())
+            .find(|index| self.items[*index].1 && self.item_rect(world, *index).contains(point));
+
+        match hovered {
+            Some(index) if index != self.selected => {
+                self.selected = index;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draws a title above the list, then every item using `label` for its text.
+    pub fn draw<F>(
+        &self,
+        world: &World,
+        ctx: &mut ggez::Context,
+        title: &str,
+        label: F,
+    ) -> ggez::GameResult<()>
+    where
+        F: Fn(T) -> String,
+    {
+        let title_rect = self.item_rect(world, 0);
+        let title = graphics::Text::new(
+            graphics::TextFragment::new(title)
+                .font(self.font)
+                .scale(graphics::Scale::uniform(64.0)),
+        );
+
+        graphics::draw(
+            ctx,
+            &title,
+            (
+                na::Point2::new(title_rect.x, title_rect.y - 96.0),
+                graphics::WHITE,
+            ),
+        )?;
+
+        for (index, (item, enabled)) in self.items.iter().enumerate() {
+            let rect = self.item_rect(world, index);
+
+            let color = if !enabled {
+                graphics::Color::new(0.5, 0.5, 0.5, 1.0)
+            } else if index == self.selected {
+                graphics::Color::new(1.0, 0.85, 0.2, 1.0)
+            } else {
+                graphics::WHITE
+            };
+
+            let text = graphics::Text::new(
+                graphics::TextFragment::new(label(*item))
+                    .font(self.font)
+                    .scale(graphics::Scale::uniform(self.item_height * 2.0 / 3.0)),
+            );
+
+            graphics::draw(ctx, &text, (na::Point2::new(rect.x, rect.y), color))?;
+        }
+
+        Ok(())
+    }
+}
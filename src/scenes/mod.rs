@@ -3,8 +3,17 @@ use ggez_goodies::scene;
 use crate::input;
 use crate::world::World;
 
+pub mod controls;
+pub mod effects;
+pub mod hud;
 pub mod level;
 pub mod menu;
+pub mod menu_list;
+pub mod path_overlay;
+pub mod pause;
+pub mod recipe_book;
+pub mod settings;
+pub mod unit_panel;
 
 // Shortcuts for our scene type.
 pub type Switch = scene::SceneSwitch<World, input::Event>;
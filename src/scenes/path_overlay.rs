@@ -0,0 +1,142 @@
+use ggez;
+use ggez::graphics;
+use ggez::nalgebra as na;
+
+use crate::render::{RenderLayer, RenderQueue};
+use crate::screen::{Transform, TILE_SIZE};
+
+/// An arrow is drawn every this many tiles along a lane.
+const ARROW_SPACING: usize = 3;
+const LINE_WIDTH: f32 = 2.0;
+
+/// Cycled through so neighbouring lanes can be told apart.
+const LANE_COLORS: [(f32, f32, f32); 3] = [(1.0, 0.85, 0.2), (0.3, 0.8, 1.0), (1.0, 0.4, 0.7)];
+
+/// The path mobs walk along each lane, with arrows pointing the way they go.
+/// The mesh is only rebuilt once the lanes change.
+pub struct PathOverlay {
+    lanes: Vec<Vec<na::Point2<i32>>>,
+    mesh: Option<graphics::Mesh>,
+}
+
+impl PathOverlay {
+    pub fn new(lanes: Vec<Vec<na::Point2<i32>>>) -> Self {
+        PathOverlay { lanes, mesh: None }
+    }
+
+    pub fn set_lanes(&mut self, lanes: Vec<Vec<na::Point2<i32>>>) {
+        self.lanes = lanes;
+        self.mesh = None;
+    }
+
+    /// Number of steps from the first waypoint to the last.
+    pub fn length(&self) -> usize {
+        self.lanes
+            .iter()
+            .map(|lane| lane.len().saturating_sub(1))
+            .sum()
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut ggez::Context,
+        transform: &Transform,
+        queue: &mut RenderQueue,
+    ) -> ggez::GameResult<()> {
+        if self.mesh.is_none() {
+            self.mesh = self.build(ctx)?;
+        }
+
+        if let Some(mesh) = &self.mesh {
+            queue.draw(
+                RenderLayer::Decals,
+                0.0,
+                mesh.clone(),
+                transform.draw_param(),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn build(&self, ctx: &mut ggez::Context) -> ggez::GameResult<Option<graphics::Mesh>> {
+        let mut builder = graphics::MeshBuilder::new();
+        let mut empty = true;
+
+        for (index, lane) in self.lanes.iter().enumerate() {
+            let (r, g, b) = LANE_COLORS[index % LANE_COLORS.len()];
+            let color = graphics::Color::new(r, g, b, 0.8);
+
+            let mut points: Vec<na::Point2<f32>> =
+                lane.iter().map(Transform::tile_center).collect();
+            points.dedup();
+
+            if points.len() < 2 {
+                continue;
+            }
+
+            builder.line(&points, LINE_WIDTH, color)?;
+            empty = false;
+
+            for step in points
+                .windows(2)
+                .skip(ARROW_SPACING / 2)
+                .step_by(ARROW_SPACING)
+            {
+                builder.polygon(
+                    graphics::DrawMode::fill(),
+                    &arrow_head(&step[0], &step[1], TILE_SIZE / 2.0),
+                    color,
+                )?;
+            }
+        }
+
+        if empty {
+            Ok(None)
+        } else {
+            builder.build(ctx).map(Some)
+        }
+    }
+}
+
+/// A triangle halfway between two points, pointing from `from` towards `to`.
+fn arrow_head(from: &na::Point2<f32>, to: &na::Point2<f32>, size: f32) -> [na::Point2<f32>; 3] {
+    let direction = (to - from).normalize();
+    let side = na::Vector2::new(-direction.y, direction.x);
+    let center = na::center(from, to);
+
+    [
+        center + direction * (size / 2.0),
+        center - direction * (size / 2.0) + side * (size / 2.0),
+        center - direction * (size / 2.0) - side * (size / 2.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_counts_steps() {
+        let overlay = PathOverlay::new(vec![
+            vec![
+                na::Point2::new(0, 0),
+                na::Point2::new(1, 0),
+                na::Point2::new(2, 1),
+            ],
+            vec![na::Point2::new(2, 1), na::Point2::new(2, 2)],
+            vec![],
+        ]);
+
+        assert_eq!(3, overlay.length());
+    }
+
+    #[test]
+    fn arrow_points_along_the_path() {
+        let arrow = arrow_head(&na::Point2::new(0.0, 0.0), &na::Point2::new(16.0, 0.0), 8.0);
+
+        assert_eq!(na::Point2::new(12.0, 0.0), arrow[0]);
+        assert_eq!(na::Point2::new(4.0, 4.0), arrow[1]);
+        assert_eq!(na::Point2::new(4.0, -4.0), arrow[2]);
+    }
+}
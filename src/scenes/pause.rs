@@ -0,0 +1,129 @@
+use ggez;
+use ggez::graphics;
+use ggez::nalgebra as na;
+use ggez_goodies::input::InputEffect;
+use ggez_goodies::scene;
+
+use crate::input;
+use crate::scenes;
+use crate::scenes::menu_list::MenuList;
+use crate::scenes::settings::SettingsScene;
+use crate::world::{PauseAction, World};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PauseItem {
+    Resume,
+    Restart,
+    Settings,
+    Save,
+    QuitToMenu,
+}
+
+const PAUSE_ITEMS: [PauseItem; 5] = [
+    PauseItem::Resume,
+    PauseItem::Restart,
+    PauseItem::Settings,
+    PauseItem::Save,
+    PauseItem::QuitToMenu,
+];
+
+impl PauseItem {
+    fn label(self) -> &'static str {
+        match self {
+            PauseItem::Resume => "Resume",
+            PauseItem::Restart => "Restart",
+            PauseItem::Settings => "Settings",
+            PauseItem::Save => "Save",
+            PauseItem::QuitToMenu => "Quit to Menu",
+        }
+    }
+}
+
+/// Pushed over the level; the level stops updating while this is on top of the stack.
+pub struct PauseScene {
+    list: MenuList<PauseItem>,
+    activated: Option<PauseItem>,
+}
+
+impl PauseScene {
+    /// Saves only capture the build phase, so `can_save` is false during combat.
+    pub fn new(_ctx: &mut ggez::Context, world: &mut World, can_save: bool) -> Self {
+        let mut list = MenuList::new(world.font, &PAUSE_ITEMS);
+        list.set_enabled(PauseItem::Save, can_save);
+
+        PauseScene {
+            list,
+            activated: None,
+        }
+    }
+}
+
+impl scene::Scene<World, input::Event> for PauseScene {
+    fn update(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> scenes::Switch {
+        let action = match self.activated.take() {
+            Some(PauseItem::Resume) => None,
+            Some(PauseItem::Restart) => Some(PauseAction::Restart),
+            Some(PauseItem::Save) => Some(PauseAction::Save),
+            Some(PauseItem::QuitToMenu) => Some(PauseAction::QuitToMenu),
+            Some(PauseItem::Settings) => {
+                return scene::SceneSwitch::Push(Box::new(SettingsScene::new(ctx, gameworld)));
+            }
+            None => return scene::SceneSwitch::None,
+        };
+
+        gameworld.pause_action = action;
+        scene::SceneSwitch::Pop
+    }
+
+    fn draw(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let dim = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::screen_coordinates(ctx),
+            graphics::Color::new(0.0, 0.0, 0.0, 0.6),
+        )?;
+
+        graphics::draw(ctx, &dim, (na::Point2::new(0.0, 0.0),))?;
+
+        self.list
+            .draw(gameworld, ctx, "Paused", |item| item.label().to_owned())
+    }
+
+    fn name(&self) -> &str {
+        "PauseScene"
+    }
+
+    fn draw_previous(&self) -> bool {
+        true
+    }
+
+    fn input(&mut self, gameworld: &mut World, ev: input::Event, _started: bool) {
+        match ev {
+            input::InputEvent::InputEffect(InputEffect::Axis(input::Axis::Vert, up)) => {
+                if gameworld.input.get_axis_raw(input::Axis::Vert) != 0.0 {
+                    self.list.move_selection(up);
+                }
+            }
+            input::InputEvent::InputEffect(_) => {
+                if gameworld.input.get_button_pressed(input::Button::Select) {
+                    let item = self.list.selected();
+
+                    if self.list.is_enabled(item) {
+                        self.activated = Some(item);
+                    }
+                }
+
+                if gameworld.input.get_button_pressed(input::Button::Menu)
+                    || gameworld.input.get_button_pressed(input::Button::Quit)
+                {
+                    self.activated = Some(PauseItem::Resume);
+                }
+            }
+            input::InputEvent::MouseEffect(effect) => {
+                self.list
+                    .hover(gameworld, na::Point2::new(effect.x, effect.y));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use ggez;
+use ggez::graphics;
+use ggez::nalgebra as na;
+
+use crate::game::combination::{recipes, Recipe};
+use crate::game::unit::{Unit, UnitType};
+use crate::spritesheet::{SpriteLayer, Tile, TileMap};
+
+const WIDTH: f32 = 340.0;
+/// Leaves room for the HUD above.
+const TOP: f32 = 200.0;
+const MARGIN: f32 = 8.0;
+const TITLE_HEIGHT: f32 = 22.0;
+const ROW_HEIGHT: f32 = 60.0;
+const ICON_SCALE: f32 = 2.0;
+const ICON_SIZE: f32 = 16.0 * ICON_SCALE;
+const INGREDIENT_WIDTH: f32 = (WIDTH - MARGIN * 2.0) / 3.0;
+
+/// Lists every recipe with its ingredients, on the right side of the screen.
+/// Recipes that can be made from the units on the board are highlighted.
+pub struct RecipeBook {
+    font: graphics::Font,
+    icons: SpriteLayer,
+}
+
+impl RecipeBook {
+    pub fn new(font: graphics::Font, tilemap: TileMap) -> Self {
+        RecipeBook {
+            font,
+            icons: SpriteLayer::new(tilemap),
+        }
+    }
+
+    fn rect(screen: na::Vector2<f32>) -> graphics::Rect {
+        graphics::Rect::new(
+            screen.x - WIDTH - MARGIN,
+            TOP,
+            WIDTH,
+            TITLE_HEIGHT + recipes().len() as f32 * ROW_HEIGHT + MARGIN * 2.0,
+        )
+    }
+
+    fn row_rect(screen: na::Vector2<f32>, index: usize) -> graphics::Rect {
+        let book = RecipeBook::rect(screen);
+
+        graphics::Rect::new(
+            book.x + MARGIN,
+            book.y + MARGIN + TITLE_HEIGHT + index as f32 * ROW_HEIGHT,
+            WIDTH - MARGIN * 2.0,
+            ROW_HEIGHT,
+        )
+    }
+
+    pub fn contains(screen: na::Vector2<f32>, point: &na::Point2<f32>) -> bool {
+        RecipeBook::rect(screen).contains(*point)
+    }
+
+    /// The result of the recipe under `point`.
+    pub fn recipe_at(screen: na::Vector2<f32>, point: &na::Point2<f32>) -> Option<UnitType> {
+        recipes()
+            .iter()
+            .enumerate()
+            .find(|(index, _)| RecipeBook::row_rect(screen, *index).contains(*point))
+            .map(|(_, (result, _))| *result)
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut ggez::Context,
+        screen: na::Vector2<f32>,
+        units: &[Unit],
+        available: &[UnitType],
+        hovered: Option<UnitType>,
+    ) -> ggez::GameResult<()> {
+        let book = RecipeBook::rect(screen);
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            book,
+            graphics::Color::new(0.0, 0.0, 0.0, 0.6),
+        )?;
+
+        graphics::draw(ctx, &background, graphics::DrawParam::default())?;
+
+        let title = graphics::Text::new(("Recipes", self.font, 18.0));
+        graphics::draw(
+            ctx,
+            &title,
+            (
+                na::Point2::new(book.x + MARGIN, book.y + MARGIN),
+                graphics::WHITE,
+            ),
+        )?;
+
+        for (index, recipe) in recipes().iter().enumerate() {
+            let row = RecipeBook::row_rect(screen, index);
+            let (result, _) = recipe;
+
+            if available.contains(result) || hovered == Some(*result) {
+                let highlight = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    row,
+                    if hovered == Some(*result) {
+                        graphics::Color::new(1.0, 1.0, 0.0, 0.25)
+                    } else {
+                        graphics::Color::new(0.0, 1.0, 0.0, 0.25)
+                    },
+                )?;
+
+                graphics::draw(ctx, &highlight, graphics::DrawParam::default())?;
+            }
+
+            self.draw_recipe(ctx, row, recipe, units)?;
+        }
+
+        self.icons.draw(
+            ctx,
+            graphics::DrawParam::default().scale(na::Vector2::new(ICON_SCALE, ICON_SCALE)),
+        )?;
+        self.icons.clear();
+
+        Ok(())
+    }
+
+    fn draw_recipe(
+        &mut self,
+        ctx: &mut ggez::Context,
+        row: graphics::Rect,
+        (result, requirements): &Recipe,
+        units: &[Unit],
+    ) -> ggez::GameResult<()> {
+        let name = graphics::Text::new((format!("{:?}", result), self.font, 16.0));
+        graphics::draw(
+            ctx,
+            &name,
+            (na::Point2::new(row.x, row.y + 2.0), graphics::WHITE),
+        )?;
+
+        for (i, (unit_type, rank)) in requirements.iter().enumerate() {
+            let cell = na::Point2::new(row.x + i as f32 * INGREDIENT_WIDTH, row.y + 22.0);
+            let on_board = units
+                .iter()
+                .any(|unit| unit.unit_type == *unit_type && unit.rank == *rank);
+
+            // The icons are drawn scaled up in one batch, so their positions are scaled down.
+            self.icons.add(
+                &Tile {
+                    sprite_layer: 0,
+                    sprite_id: unit_type.sprite_id(),
+                },
+                cell.x / ICON_SCALE,
+                cell.y / ICON_SCALE,
+            );
+
+            let label =
+                graphics::Text::new((format!("{:?}\nRank {}", unit_type, rank), self.font, 11.0));
+
+            graphics::draw(
+                ctx,
+                &label,
+                (
+                    na::Point2::new(cell.x + ICON_SIZE + 2.0, cell.y + 2.0),
+                    if on_board {
+                        graphics::WHITE
+                    } else {
+                        graphics::Color::new(0.5, 0.5, 0.5, 1.0)
+                    },
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipes_are_found_by_position() {
+        let screen = na::Vector2::new(1280.0, 720.0);
+        let second = RecipeBook::row_rect(screen, 1);
+
+        assert_eq!(
+            Some(recipes()[1].0),
+            RecipeBook::recipe_at(screen, &na::Point2::new(second.x + 1.0, second.y + 1.0))
+        );
+        assert_eq!(
+            None,
+            RecipeBook::recipe_at(screen, &na::Point2::new(10.0, 10.0))
+        );
+    }
+}
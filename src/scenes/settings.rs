@@ -0,0 +1,216 @@
+use ggez;
+use ggez::nalgebra as na;
+use ggez_goodies::input::InputEffect;
+use ggez_goodies::scene;
+use log::*;
+
+use crate::input;
+use crate::scenes;
+use crate::scenes::controls::ControlsScene;
+use crate::scenes::menu_list::MenuList;
+use crate::screen::Screen;
+use crate::settings::{self, Settings};
+use crate::world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SettingItem {
+    Resolution,
+    Fullscreen,
+    Vsync,
+    UiScale,
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    GameSpeed,
+    Controls,
+    Back,
+}
+
+const SETTING_ITEMS: [SettingItem; 10] = [
+    SettingItem::Resolution,
+    SettingItem::Fullscreen,
+    SettingItem::Vsync,
+    SettingItem::UiScale,
+    SettingItem::MasterVolume,
+    SettingItem::MusicVolume,
+    SettingItem::EffectsVolume,
+    SettingItem::GameSpeed,
+    SettingItem::Controls,
+    SettingItem::Back,
+];
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+impl SettingItem {
+    fn label(self, settings: &Settings) -> String {
+        match self {
+            SettingItem::Resolution => {
+                format!("Resolution: {}x{}", settings.width, settings.height)
+            }
+            SettingItem::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            SettingItem::Vsync => format!("Vsync (on restart): {}", on_off(settings.vsync)),
+            SettingItem::UiScale => format!("UI Scale: {:.2}", settings.ui_scale),
+            SettingItem::MasterVolume => {
+                format!("Master Volume: {:.0}%", settings.master_volume * 100.0)
+            }
+            SettingItem::MusicVolume => {
+                format!("Music Volume: {:.0}%", settings.music_volume * 100.0)
+            }
+            SettingItem::EffectsVolume => {
+                format!("Effects Volume: {:.0}%", settings.effects_volume * 100.0)
+            }
+            SettingItem::GameSpeed => format!("Game Speed: {:.1}x", settings.game_speed),
+            SettingItem::Controls => "Controls".to_owned(),
+            SettingItem::Back => "Back".to_owned(),
+        }
+    }
+
+    /// Changes the setting one step up or down. Returns whether the window needs to be updated.
+    fn adjust(self, settings: &mut Settings, up: bool) -> bool {
+        let direction = if up { 1.0 } else { -1.0 };
+
+        match self {
+            SettingItem::Resolution => {
+                settings.next_resolution();
+                return true;
+            }
+            SettingItem::Fullscreen => {
+                settings.fullscreen = !settings.fullscreen;
+                return true;
+            }
+            SettingItem::Vsync => settings.vsync = !settings.vsync,
+            SettingItem::UiScale => {
+                settings.ui_scale = settings::step_value(
+                    settings.ui_scale,
+                    0.25 * direction,
+                    settings::MIN_UI_SCALE,
+                    settings::MAX_UI_SCALE,
+                )
+            }
+            SettingItem::MasterVolume => {
+                settings.master_volume =
+                    settings::step_value(settings.master_volume, 0.1 * direction, 0.0, 1.0)
+            }
+            SettingItem::MusicVolume => {
+                settings.music_volume =
+                    settings::step_value(settings.music_volume, 0.1 * direction, 0.0, 1.0)
+            }
+            SettingItem::EffectsVolume => {
+                settings.effects_volume =
+                    settings::step_value(settings.effects_volume, 0.1 * direction, 0.0, 1.0)
+            }
+            SettingItem::GameSpeed => {
+                settings.game_speed = settings::step_value(
+                    settings.game_speed,
+                    0.5 * direction,
+                    settings::MIN_GAME_SPEED,
+                    settings::MAX_GAME_SPEED,
+                )
+            }
+            SettingItem::Controls | SettingItem::Back => {}
+        }
+
+        false
+    }
+}
+
+/// Edits `World::settings` in place, so changes apply live and get saved on the way out.
+pub struct SettingsScene {
+    list: MenuList<SettingItem>,
+    window_changed: bool,
+    open_controls: bool,
+    done: bool,
+}
+
+impl SettingsScene {
+    pub fn new(_ctx: &mut ggez::Context, world: &mut World) -> Self {
+        SettingsScene {
+            list: MenuList::new(world.font, &SETTING_ITEMS),
+            window_changed: false,
+            open_controls: false,
+            done: false,
+        }
+    }
+}
+
+impl scene::Scene<World, input::Event> for SettingsScene {
+    fn update(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> scenes::Switch {
+        if self.window_changed {
+            self.window_changed = false;
+
+            if let Err(e) = gameworld.settings.apply(ctx) {
+                warn!("Could not apply settings: {}", e);
+            }
+
+            gameworld.screen = Screen::new(ctx);
+        }
+
+        if self.open_controls {
+            self.open_controls = false;
+
+            return scene::SceneSwitch::Push(Box::new(ControlsScene::new(ctx, gameworld)));
+        }
+
+        if self.done {
+            if let Err(e) = gameworld.settings.save(ctx) {
+                warn!("Could not save settings: {}", e);
+            }
+
+            scene::SceneSwitch::Pop
+        } else {
+            scene::SceneSwitch::None
+        }
+    }
+
+    fn draw(&mut self, gameworld: &mut World, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        let settings = &gameworld.settings;
+
+        self.list
+            .draw(gameworld, ctx, "Settings", |item| item.label(settings))
+    }
+
+    fn name(&self) -> &str {
+        "SettingsScene"
+    }
+
+    fn input(&mut self, gameworld: &mut World, ev: input::Event, _started: bool) {
+        match ev {
+            input::InputEvent::InputEffect(InputEffect::Axis(input::Axis::Vert, up)) => {
+                if gameworld.input.get_axis_raw(input::Axis::Vert) != 0.0 {
+                    self.list.move_selection(up);
+                }
+            }
+            input::InputEvent::InputEffect(InputEffect::Axis(input::Axis::Horz, up)) => {
+                if gameworld.input.get_axis_raw(input::Axis::Horz) != 0.0 {
+                    self.window_changed |= self.list.selected().adjust(&mut gameworld.settings, up);
+                }
+            }
+            input::InputEvent::InputEffect(_) => {
+                if gameworld.input.get_button_pressed(input::Button::Select) {
+                    match self.list.selected() {
+                        SettingItem::Controls => self.open_controls = true,
+                        SettingItem::Back => self.done = true,
+                        item => {
+                            self.window_changed |= item.adjust(&mut gameworld.settings, true);
+                        }
+                    }
+                }
+
+                if gameworld.input.get_button_pressed(input::Button::Quit) {
+                    self.done = true;
+                }
+            }
+            input::InputEvent::MouseEffect(effect) => {
+                self.list
+                    .hover(gameworld, na::Point2::new(effect.x, effect.y));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use ggez;
+use ggez::graphics;
+use ggez::nalgebra as na;
+
+use crate::game::unit::{Unit, UnitType};
+
+const WIDTH: f32 = 260.0;
+const LINE_HEIGHT: f32 = 20.0;
+const BUTTON_HEIGHT: f32 = 28.0;
+const MARGIN: f32 = 8.0;
+/// Every unit gets the same number of stat lines, so the panel never changes size.
+const STAT_LINES: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PanelButton {
+    Sell,
+    Combine,
+    Targeting,
+}
+
+const BUTTONS: [PanelButton; 3] = [
+    PanelButton::Sell,
+    PanelButton::Combine,
+    PanelButton::Targeting,
+];
+
+/// Stats and actions for the selected unit, in the bottom left corner of the screen.
+pub struct UnitPanel {
+    font: graphics::Font,
+}
+
+impl UnitPanel {
+    pub fn new(font: graphics::Font) -> Self {
+        UnitPanel { font }
+    }
+
+    fn rect(screen: na::Vector2<f32>) -> graphics::Rect {
+        let height = STAT_LINES as f32 * LINE_HEIGHT
+            + BUTTONS.len() as f32 * (BUTTON_HEIGHT + MARGIN)
+            + MARGIN * 2.0;
+
+        graphics::Rect::new(MARGIN, screen.y - height - MARGIN, WIDTH, height)
+    }
+
+    fn button_rect(screen: na::Vector2<f32>, index: usize) -> graphics::Rect {
+        let panel = UnitPanel::rect(screen);
+
+        graphics::Rect::new(
+            panel.x + MARGIN,
+            panel.y
+                + MARGIN
+                + STAT_LINES as f32 * LINE_HEIGHT
+                + index as f32 * (BUTTON_HEIGHT + MARGIN),
+            WIDTH - MARGIN * 2.0,
+            BUTTON_HEIGHT,
+        )
+    }
+
+    pub fn contains(screen: na::Vector2<f32>, point: &na::Point2<f32>) -> bool {
+        UnitPanel::rect(screen).contains(*point)
+    }
+
+    pub fn button_at(screen: na::Vector2<f32>, point: &na::Point2<f32>) -> Option<PanelButton> {
+        BUTTONS
+            .iter()
+            .enumerate()
+            .find(|(index, _)| UnitPanel::button_rect(screen, *index).contains(*point))
+            .map(|(_, button)| *button)
+    }
+
+    /// Sell and combine are only available while building, so `can_edit` greys them out otherwise.
+    pub fn draw(
+        &self,
+        ctx: &mut ggez::Context,
+        screen: na::Vector2<f32>,
+        unit: &Unit,
+        combinations: &[UnitType],
+        ticks_per_second: f32,
+        can_edit: bool,
+    ) -> ggez::GameResult<()> {
+        let panel = UnitPanel::rect(screen);
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            panel,
+            graphics::Color::new(0.0, 0.0, 0.0, 0.6),
+        )?;
+
+        graphics::draw(ctx, &background, graphics::DrawParam::default())?;
+
+        for (i, line) in stat_lines(unit, combinations, ticks_per_second)
+            .into_iter()
+            .enumerate()
+        {
+            let text = graphics::Text::new((line, self.font, 16.0));
+
+            graphics::draw(
+                ctx,
+                &text,
+                (
+                    na::Point2::new(panel.x + MARGIN, panel.y + MARGIN + i as f32 * LINE_HEIGHT),
+                    graphics::WHITE,
+                ),
+            )?;
+        }
+
+        for (i, button) in BUTTONS.iter().enumerate() {
+            let rect = UnitPanel::button_rect(screen, i);
+            let enabled = can_edit || *button == PanelButton::Targeting;
+
+            let outline = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(2.0),
+                rect,
+                graphics::WHITE,
+            )?;
+
+            let mut label = graphics::Text::new((button_label(*button, unit), self.font, 16.0));
+            label.set_bounds(na::Point2::new(rect.w, rect.h), graphics::Align::Center);
+
+            let color = if enabled {
+                graphics::WHITE
+            } else {
+                graphics::Color::new(0.5, 0.5, 0.5, 1.0)
+            };
+
+            graphics::draw(ctx, &outline, (na::Point2::new(0.0, 0.0), color))?;
+            graphics::draw(ctx, &label, (na::Point2::new(rect.x, rect.y + 5.0), color))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn stat_lines(unit: &Unit, combinations: &[UnitType], ticks_per_second: f32) -> Vec<String> {
+    let combinations = if combinations.is_empty() {
+        "none".to_owned()
+    } else {
+        combinations
+            .iter()
+            .map(|unit_type| format!("{:?}", unit_type))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    vec![
+        format!("{:?} - Rank {}", unit.unit_type, unit.rank),
+        format!("Damage: {}", unit.damage),
+        format!(
+            "Attack speed: {:.2}/s",
+            unit.effective_attack_speed(ticks_per_second)
+        ),
+        format!("Range: {:.0}", unit.range),
+        format!("Damage type: {:?}", unit.damage_type),
+        format!("Targets: {:?}", unit.targets),
+        format!("Kills: {}", unit.kills),
+        format!("Damage dealt: {}", unit.damage_dealt),
+        format!("Combines into: {}", combinations),
+    ]
+}
+
+fn button_label(button: PanelButton, unit: &Unit) -> String {
+    match button {
+        PanelButton::Sell => "Sell".to_owned(),
+        PanelButton::Combine => "Combine".to_owned(),
+        PanelButton::Targeting => format!("Targeting: {:?}", unit.target_priority),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat_lines_fill_the_panel() {
+        let unit = Unit {
+            attacks: true,
+            attack_speed: 0.75,
+            ..Unit::default()
+        };
+
+        let lines = stat_lines(&unit, &[UnitType::Knight, UnitType::DarkKnight], 60.0);
+
+        assert_eq!(STAT_LINES, lines.len());
+        assert_eq!("Attack speed: 0.75/s", lines[2]);
+        assert_eq!("Combines into: Knight, DarkKnight", lines[8]);
+    }
+
+    #[test]
+    fn buttons_are_found_by_position() {
+        let screen = na::Vector2::new(800.0, 600.0);
+        let targeting = UnitPanel::button_rect(screen, 2);
+
+        assert_eq!(
+            Some(PanelButton::Targeting),
+            UnitPanel::button_at(
+                screen,
+                &na::Point2::new(targeting.x + 1.0, targeting.y + 1.0)
+            )
+        );
+        assert_eq!(
+            None,
+            UnitPanel::button_at(screen, &na::Point2::new(400.0, 300.0))
+        );
+        assert!(!UnitPanel::contains(screen, &na::Point2::new(400.0, 300.0)));
+    }
+}
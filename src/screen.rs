@@ -1,12 +1,13 @@
 use ggez::nalgebra as na;
 use ggez::{graphics, Context};
 
+/// Width and height of a board tile in world pixels.
+pub const TILE_SIZE: f32 = 16.0;
+
 pub struct Screen {
     width: f32,
     height: f32,
-    #[allow(dead_code)]
     scaled_width: f32,
-    #[allow(dead_code)]
     scaled_height: f32,
 }
 
@@ -26,11 +27,19 @@ impl Screen {
         }
     }
 
-    #[allow(dead_code)]
     pub fn to_screen_coordinate(&self, point: na::Point2<f32>) -> na::Point2<f32> {
         na::Point2::new(point.x * self.scaled_width, point.y * self.scaled_height)
     }
 
+    /// The inverse of `to_screen_coordinate`, for window positions reported in drawable pixels.
+    pub fn to_logical_coordinate(&self, point: na::Point2<f32>) -> na::Point2<f32> {
+        na::Point2::new(point.x / self.scaled_width, point.y / self.scaled_height)
+    }
+
+    pub fn dimensions(&self) -> na::Vector2<f32> {
+        na::Vector2::new(self.width, self.height)
+    }
+
     pub fn center_fit(&self, rect: &graphics::Rect) -> graphics::Rect {
         let side_margins = (self.width - rect.w) / 2.0;
         let height_margins = (self.height - rect.h) / 2.0;
@@ -38,3 +47,95 @@ impl Screen {
         graphics::Rect::new(side_margins, height_margins, rect.w, rect.h)
     }
 }
+
+/// Maps between the coordinate spaces of the board:
+/// tiles, world pixels (tile (0, 0) covers (0, 0) to (16, 16)) and logical screen pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Where the world origin ends up on screen.
+    pub origin: na::Point2<f32>,
+    /// Screen pixels per world pixel.
+    pub scale: f32,
+}
+
+impl Transform {
+    /// The top left corner of a tile.
+    pub fn tile_to_world(tile: &na::Point2<i32>) -> na::Point2<f32> {
+        na::Point2::new(tile.x as f32 * TILE_SIZE, tile.y as f32 * TILE_SIZE)
+    }
+
+    pub fn tile_center(tile: &na::Point2<i32>) -> na::Point2<f32> {
+        Transform::tile_to_world(tile) + na::Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0)
+    }
+
+    pub fn world_to_tile(point: &na::Point2<f32>) -> na::Point2<i32> {
+        na::Point2::new(
+            (point.x / TILE_SIZE).floor() as i32,
+            (point.y / TILE_SIZE).floor() as i32,
+        )
+    }
+
+    pub fn world_to_screen(&self, point: &na::Point2<f32>) -> na::Point2<f32> {
+        self.origin + point.coords * self.scale
+    }
+
+    pub fn screen_to_world(&self, point: &na::Point2<f32>) -> na::Point2<f32> {
+        na::Point2::from((point - self.origin) / self.scale)
+    }
+
+    pub fn tile_to_screen(&self, tile: &na::Point2<i32>) -> na::Point2<f32> {
+        self.world_to_screen(&Transform::tile_to_world(tile))
+    }
+
+    pub fn screen_to_tile(&self, point: &na::Point2<f32>) -> na::Point2<i32> {
+        Transform::world_to_tile(&self.screen_to_world(point))
+    }
+
+    /// Draws something given in world pixels at its place on screen.
+    pub fn draw_param(&self) -> graphics::DrawParam {
+        graphics::DrawParam::default()
+            .dest(self.origin)
+            .scale(na::Vector2::new(self.scale, self.scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_transform() -> Transform {
+        Transform {
+            origin: na::Point2::new(100.0, 50.0),
+            scale: 1.5,
+        }
+    }
+
+    #[test]
+    fn tiles_round_trip_through_screen() {
+        let transform = make_transform();
+        let tile = na::Point2::new(7, 31);
+
+        assert_eq!(
+            tile,
+            transform.screen_to_tile(&transform.tile_to_screen(&tile))
+        );
+        assert_eq!(
+            tile,
+            transform.screen_to_tile(&transform.world_to_screen(&Transform::tile_center(&tile)))
+        );
+    }
+
+    #[test]
+    fn tile_edges_belong_to_the_next_tile() {
+        let transform = make_transform();
+
+        assert_eq!(
+            na::Point2::new(-1, -1),
+            transform.screen_to_tile(&na::Point2::new(99.0, 49.0))
+        );
+        assert_eq!(
+            na::Point2::new(1, 0),
+            transform.screen_to_tile(&na::Point2::new(100.0 + 24.0, 50.0))
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::io::Write;
+
+use ggez::{conf, graphics, Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Error;
+
+/// Kept in the user config directory, next to ggez's own `conf.toml`.
+const SETTINGS_PATH: &str = "/settings.ron";
+/// ggez reads this when building the context, which is the only way to get vsync applied.
+const CONF_PATH: &str = "/conf.toml";
+
+pub const RESOLUTIONS: [(f32, f32); 4] = [
+    (1280.0, 1024.0),
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+];
+
+pub const MIN_UI_SCALE: f32 = 1.0;
+pub const MAX_UI_SCALE: f32 = 3.0;
+pub const MIN_GAME_SPEED: f32 = 0.5;
+pub const MAX_GAME_SPEED: f32 = 3.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub width: f32,
+    pub height: f32,
+    pub fullscreen: bool,
+    /// Only takes effect the next time the game starts.
+    pub vsync: bool,
+    pub ui_scale: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    /// Multiplier on the number of simulation updates per second.
+    pub game_speed: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            width: 1280.0,
+            height: 1024.0,
+            fullscreen: false,
+            vsync: true,
+            ui_scale: 1.5,
+            master_volume: 1.0,
+            music_volume: 0.8,
+            effects_volume: 0.8,
+            game_speed: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the saved settings, falling back to the defaults if there are none yet.
+    pub fn load(ctx: &mut Context) -> Result<Self, Error> {
+        if !ggez::filesystem::exists(ctx, SETTINGS_PATH) {
+            return Ok(Settings::default());
+        }
+
+        let file = ggez::filesystem::open(ctx, SETTINGS_PATH).map_err(Error::GgezError)?;
+
+        ron::de::from_reader(file).map_err(Error::DeserializeError)
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> Result<(), Error> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(Error::SerializeError)?;
+        let mut file = ggez::filesystem::create(ctx, SETTINGS_PATH).map_err(Error::GgezError)?;
+
+        file.write_all(ron.as_bytes()).map_err(Error::IoError)?;
+
+        let mut conf_file = ggez::filesystem::create(ctx, CONF_PATH).map_err(Error::GgezError)?;
+
+        self.conf()
+            .to_toml_file(&mut conf_file)
+            .map_err(Error::GgezError)
+    }
+
+    pub fn window_setup(&self) -> conf::WindowSetup {
+        conf::WindowSetup::default()
+            .title("Tower")
+            .vsync(self.vsync)
+    }
+
+    pub fn window_mode(&self) -> conf::WindowMode {
+        let fullscreen_type = if self.fullscreen {
+            conf::FullscreenType::True
+        } else {
+            conf::FullscreenType::Windowed
+        };
+
+        conf::WindowMode::default()
+            .dimensions(self.width, self.height)
+            .fullscreen_type(fullscreen_type)
+    }
+
+    pub fn conf(&self) -> conf::Conf {
+        let mut conf = conf::Conf::new();
+
+        conf.window_setup = self.window_setup();
+        conf.window_mode = self.window_mode();
+
+        conf
+    }
+
+    /// Applies everything ggez can change on a running window.
+    pub fn apply(&self, ctx: &mut Context) -> GameResult<()> {
+        graphics::set_mode(ctx, self.window_mode())?;
+        graphics::set_screen_coordinates(
+            ctx,
+            graphics::Rect::new(0.0, 0.0, self.width, self.height),
+        )
+    }
+
+    pub fn next_resolution(&mut self) {
+        let current = RESOLUTIONS
+            .iter()
+            .position(|(width, height)| *width == self.width && *height == self.height);
+        let (width, height) = match current {
+            Some(index) => RESOLUTIONS[(index + 1) % RESOLUTIONS.len()],
+            None => RESOLUTIONS[0],
+        };
+
+        self.width = width;
+        self.height = height;
+    }
+}
+
+/// Moves `value` by `step`, keeping it within `min..=max` and free of float drift.
+pub fn step_value(value: f32, step: f32, min: f32, max: f32) -> f32 {
+    (((value + step) * 100.0).round() / 100.0).max(min).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let settings: Settings = ron::de::from_str("(vsync: false, game_speed: 2.0)").unwrap();
+
+        assert!(!settings.vsync);
+        assert_eq!(settings.game_speed, 2.0);
+        assert_eq!(settings.width, Settings::default().width);
+        assert_eq!(settings.ui_scale, Settings::default().ui_scale);
+    }
+
+    #[test]
+    fn resolutions_cycle() {
+        let mut settings = Settings::default();
+
+        for _ in 0..RESOLUTIONS.len() {
+            settings.next_resolution();
+        }
+
+        assert_eq!((settings.width, settings.height), RESOLUTIONS[0]);
+
+        settings.width = 800.0;
+        settings.next_resolution();
+
+        assert_eq!((settings.width, settings.height), RESOLUTIONS[0]);
+    }
+
+    #[test]
+    fn steps_are_clamped() {
+        assert_eq!(step_value(1.0, 0.1, 0.0, 1.0), 1.0);
+        assert_eq!(step_value(0.0, -0.1, 0.0, 1.0), 0.0);
+        assert_eq!(step_value(0.7, 0.1, 0.0, 1.0), 0.8);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use ggez::graphics::{
     self,
     spritebatch::{SpriteBatch, SpriteIdx},
@@ -6,61 +8,237 @@ use ggez::nalgebra as na;
 
 #[derive(Hash, Eq, PartialEq)]
 pub struct Tile {
+    /// Index of the sheet within the `SpriteLayer`.
     pub sprite_layer: i32,
     pub sprite_id: i32,
 }
 
+/// How sprites are packed on a sheet, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SheetLayout {
+    pub sprite_width: u32,
+    pub sprite_height: u32,
+    /// Empty border around the whole sheet.
+    pub margin: u32,
+    /// Gap between neighbouring sprites.
+    pub spacing: u32,
+}
+
+impl SheetLayout {
+    pub fn square(sprite_dimensions: u32) -> Self {
+        SheetLayout {
+            sprite_width: sprite_dimensions,
+            sprite_height: sprite_dimensions,
+            margin: 0,
+            spacing: 0,
+        }
+    }
+}
+
+/// The sprites on a sheet of a given size, numbered row by row from the top left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SheetGrid {
+    pub layout: SheetLayout,
+    pub width: u32,
+    pub height: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl SheetGrid {
+    pub fn new(layout: SheetLayout, width: u32, height: u32) -> Self {
+        let fit = |size: u32, sprite_size: u32| {
+            (size.saturating_sub(layout.margin * 2) + layout.spacing)
+                / (sprite_size + layout.spacing)
+        };
+
+        SheetGrid {
+            layout,
+            width,
+            height,
+            columns: fit(width, layout.sprite_width),
+            rows: fit(height, layout.sprite_height),
+        }
+    }
+
+    pub fn sprite_count(&self) -> u32 {
+        self.columns * self.rows
+    }
+
+    /// The part of the sheet a sprite covers in UV coordinates, or none if there is no such sprite.
+    pub fn uv(&self, sprite_id: i32) -> Option<graphics::Rect> {
+        if sprite_id < 0 || sprite_id as u32 >= self.sprite_count() {
+            return None;
+        }
+
+        let layout = &self.layout;
+        let column = sprite_id as u32 % self.columns;
+        let row = sprite_id as u32 / self.columns;
+        let x = layout.margin + column * (layout.sprite_width + layout.spacing);
+        let y = layout.margin + row * (layout.sprite_height + layout.spacing);
+
+        Some(graphics::Rect::new(
+            x as f32 / self.width as f32,
+            y as f32 / self.height as f32,
+            layout.sprite_width as f32 / self.width as f32,
+            layout.sprite_height as f32 / self.height as f32,
+        ))
+    }
+}
+
 #[derive(Clone)]
 pub struct TileMap {
-    pub sprite_dimensions: u32,
-    pub num_tiles_x: u32,
-    pub num_tiles_y: u32,
+    pub grid: SheetGrid,
     pub image: graphics::Image,
+    names: HashMap<String, i32>,
 }
 
 impl TileMap {
+    /// A sheet of square sprites packed edge to edge.
     pub fn new(image: graphics::Image, sprite_dimensions: u32) -> Self {
+        TileMap::with_layout(image, SheetLayout::square(sprite_dimensions))
+    }
+
+    pub fn with_layout(image: graphics::Image, layout: SheetLayout) -> Self {
         TileMap {
-            sprite_dimensions,
-            num_tiles_x: image.width() as u32 / sprite_dimensions,
-            num_tiles_y: image.height() as u32 / sprite_dimensions,
+            grid: SheetGrid::new(layout, u32::from(image.width()), u32::from(image.height())),
             image,
+            names: HashMap::new(),
         }
     }
+
+    /// Lets a sprite be looked up by name.
+    pub fn name(mut self, name: &str, sprite_id: i32) -> Self {
+        self.names.insert(name.to_owned(), sprite_id);
+        self
+    }
+
+    pub fn sprite_id(&self, name: &str) -> Option<i32> {
+        self.names.get(name).copied()
+    }
 }
 
+/// Batches sprites from one or more sheets, addressed by `Tile::sprite_layer`.
 pub struct SpriteLayer {
-    tilemap: TileMap,
-    pub batch: SpriteBatch,
+    sheets: Vec<(TileMap, SpriteBatch)>,
 }
 
 impl SpriteLayer {
     pub fn new(tilemap: TileMap) -> Self {
-        let image = tilemap.image.clone();
+        SpriteLayer::with_sheets(vec![tilemap])
+    }
 
+    pub fn with_sheets(tilemaps: Vec<TileMap>) -> Self {
         SpriteLayer {
-            tilemap,
-            batch: SpriteBatch::new(image),
+            sheets: tilemaps
+                .into_iter()
+                .map(|tilemap| {
+                    let batch = SpriteBatch::new(tilemap.image.clone());
+                    (tilemap, batch)
+                })
+                .collect(),
         }
     }
 
-    pub fn add(&mut self, tile: &Tile, x: f32, y: f32) -> SpriteIdx {
-        let sprite_x = tile.sprite_id as usize % self.tilemap.num_tiles_x as usize;
-        let sprite_y = tile.sprite_id as usize / self.tilemap.num_tiles_y as usize;
+    /// Looks a named sprite up on each sheet in turn.
+    pub fn tile(&self, name: &str) -> Option<Tile> {
+        self.sheets
+            .iter()
+            .enumerate()
+            .find_map(|(index, (tilemap, _))| {
+                tilemap.sprite_id(name).map(|sprite_id| Tile {
+                    sprite_layer: index as i32,
+                    sprite_id,
+                })
+            })
+    }
+
+    pub fn add(&mut self, tile: &Tile, x: f32, y: f32) -> Option<SpriteIdx> {
+        self.add_tinted(tile, x, y, graphics::WHITE)
+    }
+
+    /// Sprites missing from their sheet are skipped.
+    pub fn add_tinted(
+        &mut self,
+        tile: &Tile,
+        x: f32,
+        y: f32,
+        color: graphics::Color,
+    ) -> Option<SpriteIdx> {
+        let (tilemap, batch) = self.sheets.get_mut(tile.sprite_layer as usize)?;
+        let uv = tilemap.grid.uv(tile.sprite_id)?;
 
         let draw_param = graphics::DrawParam::default()
-            .src(graphics::Rect::new(
-                (1.0 / self.tilemap.num_tiles_x as f32) * sprite_x as f32,
-                (1.0 / self.tilemap.num_tiles_y as f32) * sprite_y as f32,
-                1.0 / self.tilemap.num_tiles_x as f32,
-                1.0 / self.tilemap.num_tiles_y as f32,
-            ))
-            .dest(na::Point2::new(x, y));
+            .src(uv)
+            .dest(na::Point2::new(x, y))
+            .color(color);
+
+        Some(batch.add(draw_param))
+    }
 
-        self.batch.add(draw_param)
+    /// Draws every sheet's batch, in the order the sheets were given.
+    pub fn draw(
+        &self,
+        ctx: &mut ggez::Context,
+        param: graphics::DrawParam,
+    ) -> ggez::GameResult<()> {
+        for (_, batch) in &self.sheets {
+            graphics::draw(ctx, batch, param)?;
+        }
+
+        Ok(())
     }
 
     pub fn clear(&mut self) {
-        self.batch.clear();
+        for (_, batch) in self.sheets.iter_mut() {
+            batch.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_uses_columns_on_non_square_sheets() {
+        let grid = SheetGrid::new(SheetLayout::square(16), 192, 336);
+
+        assert_eq!(12, grid.columns);
+        assert_eq!(21, grid.rows);
+        assert_eq!(
+            Some(graphics::Rect::new(
+                32.0 / 192.0,
+                256.0 / 336.0,
+                16.0 / 192.0,
+                16.0 / 336.0
+            )),
+            grid.uv(194)
+        );
+        assert_eq!(None, grid.uv(252));
+        assert_eq!(None, grid.uv(-1));
+    }
+
+    #[test]
+    fn uv_skips_margin_and_spacing() {
+        let layout = SheetLayout {
+            sprite_width: 8,
+            sprite_height: 16,
+            margin: 2,
+            spacing: 1,
+        };
+        let grid = SheetGrid::new(layout, 40, 40);
+
+        assert_eq!(4, grid.columns);
+        assert_eq!(2, grid.rows);
+        assert_eq!(
+            Some(graphics::Rect::new(
+                20.0 / 40.0,
+                19.0 / 40.0,
+                8.0 / 40.0,
+                16.0 / 40.0
+            )),
+            grid.uv(6)
+        );
     }
 }
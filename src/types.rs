@@ -3,12 +3,20 @@ use std::fmt;
 pub use ggez_goodies::Point2;
 pub use ggez_goodies::Vector2;
 
+use crate::game::unit::UnitType;
+
 /// This is not actually used very many places,
 /// but is still useful.
 #[derive(Debug)]
 pub enum Error {
     GgezError(ggez::GameError),
     DeserializeError(ron::de::Error),
+    SerializeError(ron::ser::Error),
+    IoError(std::io::Error),
+    ResourceError(String),
+    MissingUnitDefinition(UnitType),
+    DuplicateUnitDefinition(UnitType),
+    DuplicateMobDefinition(String),
 }
 
 impl fmt::Display for Error {
@@ -16,6 +24,16 @@ impl fmt::Display for Error {
         match *self {
             Error::GgezError(ref e) => write!(f, "ggez error: {}", e),
             Error::DeserializeError(ref e) => write!(f, "deserialize error: {}", e),
+            Error::SerializeError(ref e) => write!(f, "serialize error: {}", e),
+            Error::IoError(ref e) => write!(f, "io error: {}", e),
+            Error::ResourceError(ref e) => write!(f, "resource error: {}", e),
+            Error::MissingUnitDefinition(ref t) => write!(f, "no unit definition for {:?}", t),
+            Error::DuplicateUnitDefinition(ref t) => {
+                write!(f, "more than one unit definition for {:?}", t)
+            }
+            Error::DuplicateMobDefinition(ref id) => {
+                write!(f, "more than one mob definition named {:?}", id)
+            }
         }
     }
 }
@@ -1,22 +1,56 @@
 use std::path;
 
-use ggez::Context;
+use ggez::{graphics, Context};
 use log::*;
 use warmy;
 
+use crate::bindings::Bindings;
 use crate::game::board::Board;
+use crate::game::events::CombatEvent;
+use crate::game::registry::{MobRegistry, UnitRegistry};
 use crate::screen::Screen;
+use crate::settings::Settings;
 use crate::{input, resources};
 
+/// Simulation ticks per second at normal speed.
+pub const DESIRED_FPS: u32 = 60;
+
+/// Seconds of game time covered by each simulation tick, whatever the game speed.
+pub const TICK_LENGTH: f32 = 1.0 / DESIRED_FPS as f32;
+
+/// What the level should do once the pause scene has popped itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PauseAction {
+    Restart,
+    Save,
+    QuitToMenu,
+}
+
 pub struct World {
     pub resources: resources::Store,
     pub input: input::State,
     pub boards: Vec<Board>,
+    /// Everything that happened on the boards this frame, cleared before the next one.
+    pub events: Vec<CombatEvent>,
     pub screen: Screen,
+    pub settings: Settings,
+    pub bindings: Bindings,
+    /// While set, key and mouse presses reach the scene unresolved, so they can be rebound.
+    pub capturing_input: bool,
+    /// Doubles the game speed on top of the one in the settings.
+    pub fast_forward: bool,
+    pub units: UnitRegistry,
+    pub mobs: MobRegistry,
+    /// Loaded once and shared by every scene that draws text.
+    pub font: graphics::Font,
+    /// Set by the pause scene for the level underneath to act on once it resumes.
+    pub pause_action: Option<PauseAction>,
+    /// Set when a game has been saved, for the main menu to enable Continue once it is back on top.
+    pub game_saved: bool,
 }
 
 impl World {
-    pub fn new(ctx: &Context, resource_dir: &path::Path) -> Self {
+    pub fn new(ctx: &mut Context, resource_dir: &path::Path) -> Self {
         // We to bridge the gap between ggez and warmy path
         // handling here; ggez assumes its own absolute paths, warmy
         // assumes system-absolute paths; so, we make warmy look in
@@ -29,16 +63,58 @@ impl World {
         info!("Setting up resource path: {:?}", resource_dir);
 
         let opt = warmy::StoreOpt::default().set_root(resource_dir);
-        let store = warmy::Store::new(opt)
+        let mut store = warmy::Store::new(opt)
             .expect("Could not create asset store?  Does the directory exist?");
 
+        let units = UnitRegistry::load(ctx, &mut store)
+            .unwrap_or_else(|e| panic!("Could not load unit definitions: {}", e));
+        let mobs = MobRegistry::load(ctx, &mut store)
+            .unwrap_or_else(|e| panic!("Could not load mob definitions: {}", e));
+
+        let settings = Settings::load(ctx).unwrap_or_else(|e| {
+            warn!("Could not load settings, using the defaults: {}", e);
+            Settings::default()
+        });
+
+        if let Err(e) = settings.apply(ctx) {
+            warn!("Could not apply settings: {}", e);
+        }
+
+        let bindings = Bindings::load(ctx).unwrap_or_else(|e| {
+            warn!("Could not load key bindings, using the defaults: {}", e);
+            Bindings::default()
+        });
+
+        let font = graphics::Font::new(ctx, "/fonts/DejaVuSerif.ttf").unwrap_or_else(|e| {
+            warn!("Could not load the font, using the default: {}", e);
+            graphics::Font::default()
+        });
+
         let screen = Screen::new(ctx);
 
         Self {
             resources: store,
             boards: vec![Board::default()],
+            events: Vec::new(),
             input: input::State::new(),
             screen,
+            settings,
+            bindings,
+            capturing_input: false,
+            fast_forward: false,
+            units,
+            mobs,
+            font,
+            pause_action: None,
+            game_saved: false,
         }
     }
+
+    /// The simulation tick rate, after the game speed and fast forward.
+    pub fn updates_per_second(&self) -> u32 {
+        let fast_forward = if self.fast_forward { 2.0 } else { 1.0 };
+        let game_speed = self.settings.game_speed * fast_forward;
+
+        (DESIRED_FPS as f32 * game_speed).round() as u32
+    }
 }